@@ -9,6 +9,12 @@ pub trait RelationalEntry<IdType> {
     // must return all keys of relations still not known in cache (CacheReference::Unknown)
     fn get_unknown_relations(&self) -> HashSet<IdType>;
 
+    // same without the relations which are mere references (eg: citations of a document)
+    // followed when resolving without references, see ResolveParams::references
+    fn get_unknown_relations_without_references(&self) -> HashSet<IdType> {
+        self.get_unknown_relations()
+    }
+
     // must update all unknown relations of the entry
     // uses the callback 'is_known' to determine from within 'update_reference'
     // if an id is now known in the calling context
@@ -65,6 +71,8 @@ pub struct ResolveParams {
     pub print: bool,
     pub query: bool,
     pub depth: usize,
+    // follow the references of the entries as well, see RelationalEntry::get_unknown_relations_without_references
+    pub references: bool,
 }
 
 /* resolve all dependencies in the cache
@@ -88,7 +96,15 @@ where
             print,
             query,
             depth: max_depth,
+            references,
         } = params;
+        let unknown_relations = |value: &ValueType| {
+            if references {
+                value.get_unknown_relations()
+            } else {
+                value.get_unknown_relations_without_references()
+            }
+        };
 
         if print {
             println!("Resolving for {:#?} with {:#?}", target, params);
@@ -106,16 +122,15 @@ where
             let mut to_update = HashSet::<IdType>::new();
 
             // Discover identifiers referenced in the cached documents
-            if last_updated_opt.is_none() {
-                for (_, doc) in self.into_iter() {
-                    to_update.extend(doc.get_unknown_relations())
-                }
-            } else {
-                let last_updated = last_updated_opt.as_mut().unwrap();
+            if let Some(last_updated) = last_updated_opt.as_mut() {
                 for id in &*last_updated {
-                    to_update.extend(self.get(id).unwrap().get_unknown_relations())
+                    to_update.extend(unknown_relations(self.get(id).unwrap()))
                 }
                 last_updated.clear();
+            } else {
+                for (_, doc) in self.into_iter() {
+                    to_update.extend(unknown_relations(doc))
+                }
             }
 
             if to_update.is_empty() {
@@ -133,12 +148,11 @@ where
             };
 
             self.update_relations(
-                |meta_id| id_doc_new.get(meta_id).is_some(),
+                |meta_id| id_doc_new.contains(meta_id),
                 |id, value, change| {
-                    last_updated_opt.as_mut().map(|last_updated| {
+                    if let Some(last_updated) = last_updated_opt.as_mut() {
                         last_updated.insert(id.clone());
-                        last_updated
-                    });
+                    }
 
                     on_rel_change(value, change);
                 },
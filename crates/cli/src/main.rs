@@ -0,0 +1,796 @@
+use clap::{Args, Parser, Subcommand};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+
+use rfc_dep_cache::{ResolveParams, ResolveTarget};
+use rfc_dep_ietf::{
+    default_source, fetch_errata, load_errata, name_to_id, set_default_source, set_query_referenced_by, set_rate_limit, set_url_config, DocFormat,
+    DocIdentifier, ErratumStatus, FixtureSource, HttpConfig, HttpSource, LookupQuery, RecordingSource, RetryPolicy,
+    RetrySource, UrlConfig,
+};
+use rfc_dep_project::error::{ProjectError, Result};
+use rfc_dep_project::{load_project, save_project, DocCache, Equivalence};
+
+#[derive(Parser, Debug)]
+#[command(name = "rfc-dep", version, about = "Lookup IETF documents and resolve their dependencies")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+
+    /// Read documents from a directory of fixtures (or a recorded archive) instead of the network
+    #[arg(long, global = true, visible_alias = "replay")]
+    fixtures: Option<PathBuf>,
+
+    /// Record every fetched document to an archive directory, replay it later with --replay
+    #[arg(long, global = true, conflicts_with = "fixtures")]
+    record: Option<PathBuf>,
+
+    /// Number of retries of failed queries (timeouts, rate limiting, server errors)
+    #[arg(long, global = true, default_value_t = RetryPolicy::default().max_retries)]
+    retries: u32,
+
+    /// Maximum number of queries per second, 0 for no limit
+    #[arg(long, global = true, default_value_t = 10.0)]
+    rate_limit: f64,
+
+    /// Don't query the documents citing the queried documents (datatracker referenced-by pages)
+    #[arg(long, global = true)]
+    no_referenced_by: bool,
+
+    /// Timeout of each query in seconds, 0 for no timeout
+    #[arg(long, global = true, default_value_t = 30)]
+    timeout: u64,
+
+    /// User-Agent header sent with every query
+    #[arg(long, global = true)]
+    user_agent: Option<String>,
+
+    /// Proxy used for every query (the HTTP_PROXY / HTTPS_PROXY variables are used otherwise)
+    #[arg(long, global = true)]
+    proxy: Option<String>,
+
+    /// Additional trusted root certificate (PEM or DER), may be repeated
+    #[arg(long = "root-cert", global = true)]
+    root_certs: Vec<PathBuf>,
+
+    /// Base url (or local directory) of the datatracker, eg: a mirror
+    #[arg(long, global = true)]
+    datatracker_url: Option<String>,
+
+    /// Base url (or local directory) of the rfc archive (rfcXXXX.txt / .xml / .html / .pdf)
+    #[arg(long, global = true)]
+    rfc_url: Option<String>,
+
+    /// Base url (or local directory) of the internet-draft archive (draft-xxx-NN.txt / .xml / .html / .pdf)
+    #[arg(long, global = true)]
+    draft_url: Option<String>,
+
+    /// Base url (or local directory) of the rfc-editor site holding the errata dump (errata.json)
+    #[arg(long, global = true)]
+    errata_url: Option<String>,
+}
+
+/* default urls replaced by the given mirrors */
+fn url_config(cli: &Cli) -> rfc_dep_ietf::error::Result<UrlConfig> {
+    let mut config = UrlConfig::default();
+    for (base, url) in [
+        (&mut config.datatracker, &cli.datatracker_url),
+        (&mut config.rfc_archive, &cli.rfc_url),
+        (&mut config.draft_archive, &cli.draft_url),
+        (&mut config.errata, &cli.errata_url),
+    ] {
+        if let Some(url) = url {
+            *base = UrlConfig::parse_base(url)?;
+        }
+    }
+
+    Ok(config)
+}
+
+/* http client shared by every query of the command */
+fn http_source(cli: &Cli) -> rfc_dep_ietf::error::Result<HttpSource> {
+    let default = HttpConfig::default();
+    HttpSource::new(&HttpConfig {
+        timeout: (cli.timeout > 0).then(|| Duration::from_secs(cli.timeout)),
+        user_agent: cli.user_agent.clone().unwrap_or(default.user_agent),
+        proxy: cli.proxy.clone(),
+        root_certificates: cli.root_certs.clone(),
+        ..HttpConfig::default()
+    })
+}
+
+/// Filters of a datatracker lookup
+#[derive(Args, Debug)]
+struct LookupFilters {
+    /// Working group acronym (eg: tcpm)
+    #[arg(long)]
+    group: Option<String>,
+    /// Area acronym (eg: wit)
+    #[arg(long)]
+    area: Option<String>,
+    /// Stream (ietf, irtf, iab, ise)
+    #[arg(long)]
+    stream: Option<String>,
+    /// Author name
+    #[arg(long)]
+    author: Option<String>,
+    /// Document state (active, expired, rfc, repl...), includes drafts
+    #[arg(long)]
+    status: Option<String>,
+    /// Modified on or after this date (YYYY-MM-DD)
+    #[arg(long)]
+    since: Option<String>,
+    /// Modified on or before this date (YYYY-MM-DD)
+    #[arg(long)]
+    until: Option<String>,
+    /// Document name prefix (eg: draft-ietf-tcpm-)
+    #[arg(long)]
+    prefix: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Lookup documents on the datatracker by title and / or filters
+    Lookup {
+        title: Option<String>,
+        #[command(flatten)]
+        filters: LookupFilters,
+        /// Maximum number of results, 0 to follow every page of results
+        #[arg(short, long, default_value_t = 100)]
+        limit: usize,
+        /// Include drafts in the results
+        #[arg(short = 'd', long)]
+        include_drafts: bool,
+        /// Print the results as json
+        #[arg(long)]
+        json: bool,
+        /// Import all results in this project (created if missing)
+        #[arg(short, long)]
+        import: Option<PathBuf>,
+    },
+    /// Import documents by exact name (rfcXXXX / draft-abcdef / bcpXX) in a project (created if missing)
+    Import {
+        project: PathBuf,
+        #[arg(required = true)]
+        names: Vec<String>,
+    },
+    /// Import every rfc and subseries of a local rfc-index.xml (https://www.rfc-editor.org/rfc-index.xml) in a project (created if missing)
+    Index {
+        project: PathBuf,
+        index: PathBuf,
+    },
+    /// Import documents from their plain text files (downloaded rfcs / drafts) in a project (created if missing)
+    ImportText {
+        project: PathBuf,
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Complete the documents of a project using their downloaded text, without any query
+    Repair {
+        project: PathBuf,
+    },
+    /// Query the given documents again (all documents if none given) when their pages changed since they were queried,
+    /// keeping their reading state and downloaded copies
+    Refresh {
+        project: PathBuf,
+        ids: Vec<String>,
+    },
+    /// Check the drafts of a project for newer revisions on the datatracker
+    Revisions {
+        project: PathBuf,
+    },
+    /// Select the latest revision of drafts, or the revision given in their name (draft-abcdef-NN)
+    /// (every draft with a newer revision found by `revisions` if none given)
+    Upgrade {
+        project: PathBuf,
+        ids: Vec<String>,
+    },
+    /// List the revisions of a draft of a project along with their dates
+    History {
+        project: PathBuf,
+        draft: String,
+    },
+    /// Print the diff between the texts of two revisions of a draft of a project
+    Diff {
+        project: PathBuf,
+        draft: String,
+        /// Old revision (eg: 03)
+        old: String,
+        /// New revision, the selected one if not given
+        new: Option<String>,
+        /// Unchanged lines shown around the changes
+        #[arg(short = 'U', long, default_value_t = 3)]
+        context: usize,
+    },
+    /// Attach their errata reports to the rfcs of a project and print their counts
+    Errata {
+        project: PathBuf,
+        /// Local copy of the errata dump (https://www.rfc-editor.org/errata.json) instead of querying it
+        #[arg(long)]
+        dump: Option<PathBuf>,
+    },
+    /// Download the given documents (all documents if none given), their copies are kept in the project
+    Download {
+        project: PathBuf,
+        ids: Vec<String>,
+        /// Format to download (txt, html, xml, pdf), documents not published in this format are downloaded as txt
+        #[arg(short, long, default_value_t = DocFormat::Text)]
+        format: DocFormat,
+        /// Also write the copies to this directory ({id}.{format})
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Resolve the dependencies of the given documents (all documents if none given)
+    Resolve {
+        project: PathBuf,
+        ids: Vec<String>,
+        /// Maximum resolve depth
+        #[arg(long, default_value_t = 1)]
+        depth: usize,
+        /// Only link relations to already cached documents
+        #[arg(long)]
+        no_query: bool,
+        /// Don't query the normative / informative references, only the other relations (updates, obsoletes...)
+        #[arg(long)]
+        no_references: bool,
+        /// Print resolve progress
+        #[arg(short, long)]
+        verbose: bool,
+    },
+    /// List the documents of a project
+    List {
+        project: PathBuf,
+        /// Only list documents with unknown relations
+        #[arg(long)]
+        missing: bool,
+        /// List an rfc, the drafts it was published from and its aliases as one document with their merged relations
+        #[arg(short, long)]
+        equivalent: bool,
+    },
+    /// Print the documents superseding the given documents up to the current one (every superseded document if none given)
+    Superseded {
+        project: PathBuf,
+        ids: Vec<String>,
+    },
+    /// Merge other projects in a project (created if missing)
+    Merge {
+        project: PathBuf,
+        #[arg(required = true)]
+        others: Vec<PathBuf>,
+    },
+    /// Write a project as json to a file or stdout
+    Export {
+        project: PathBuf,
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+/* load a project, or start an empty one if the file doesn't exist yet */
+fn load_or_default(path: &Path) -> Result<DocCache> {
+    if path.exists() {
+        load_project(path)
+    } else {
+        Ok(DocCache::default())
+    }
+}
+
+fn lookup(
+    title: Option<String>,
+    filters: LookupFilters,
+    limit: usize,
+    include_drafts: bool,
+    json: bool,
+    import: Option<PathBuf>,
+) -> Result<()> {
+    let mut query = LookupQuery::new().include_drafts(include_drafts);
+    if limit > 0 {
+        query = query.limit(limit);
+    }
+    for (value, filter) in [
+        (title, LookupQuery::title as fn(LookupQuery, String) -> LookupQuery),
+        (filters.group, LookupQuery::group),
+        (filters.area, LookupQuery::area),
+        (filters.stream, LookupQuery::stream),
+        (filters.author, LookupQuery::author),
+        (filters.status, LookupQuery::status),
+        (filters.since, LookupQuery::since),
+        (filters.until, LookupQuery::until),
+        (filters.prefix, LookupQuery::name_prefix),
+    ] {
+        if let Some(value) = value {
+            query = filter(query, value);
+        }
+    }
+
+    // malformed results are skipped
+    let source = default_source();
+    let mut pages = query.pages(source.as_ref())?;
+    let mut summaries = Vec::new();
+    for page in pages.by_ref() {
+        summaries.extend(page?);
+    }
+    for err in pages.errors() {
+        eprintln!("Warning: {}", err);
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&summaries)?);
+    } else {
+        for summary in &summaries {
+            println!("{}\trev {}\t{}", summary.id, summary.revision, summary.title);
+        }
+    }
+
+    if let Some(path) = import {
+        let mut cache = load_or_default(&path)?;
+        for err in rfc_dep_project::import_summaries(&mut cache, summaries) {
+            eprintln!("Could not import: {}", err);
+        }
+        save_project(&cache, &path)?;
+    }
+
+    Ok(())
+}
+
+fn import(project: &Path, names: Vec<String>) -> Result<()> {
+    let mut cache = load_or_default(project)?;
+    let mut failed = 0;
+    for name in names {
+        match rfc_dep_project::import_name(&mut cache, &name) {
+            Ok(id) => println!("imported {}", id),
+            Err(err) => {
+                eprintln!("Could not import {}: {}", name, err);
+                failed += 1;
+            }
+        }
+    }
+
+    save_project(&cache, project)?;
+    if failed > 0 {
+        return Err(ProjectError::Doc(format!("could not import {} documents", failed)));
+    }
+
+    Ok(())
+}
+
+fn index(project: &Path, index: &Path) -> Result<()> {
+    let mut cache = load_or_default(project)?;
+    let added = rfc_dep_project::import_rfc_index(&mut cache, index)?;
+    println!("imported {} documents", added);
+
+    save_project(&cache, project)
+}
+
+fn import_text(project: &Path, files: Vec<PathBuf>) -> Result<()> {
+    let mut cache = load_or_default(project)?;
+    let mut failed = 0;
+    for file in files {
+        let imported = std::fs::read_to_string(&file)
+            .map_err(ProjectError::from)
+            .and_then(|text| rfc_dep_project::import_text(&mut cache, text));
+        match imported {
+            Ok(id) => println!("imported {}", id),
+            Err(err) => {
+                eprintln!("Could not import {}: {}", file.display(), err);
+                failed += 1;
+            }
+        }
+    }
+
+    save_project(&cache, project)?;
+    if failed > 0 {
+        return Err(ProjectError::Doc(format!("could not import {} documents", failed)));
+    }
+
+    Ok(())
+}
+
+fn repair(project: &Path) -> Result<()> {
+    let mut cache = load_project(project)?;
+    let repaired = rfc_dep_project::repair_from_texts(&mut cache);
+    println!("repaired {} documents", repaired);
+
+    save_project(&cache, project)
+}
+
+fn refresh(project: &Path, ids: Vec<String>) -> Result<()> {
+    let mut cache = load_project(project)?;
+
+    let ids = if ids.is_empty() {
+        cache.keys().cloned().collect()
+    } else {
+        ids.into_iter()
+            .map(|name| name_to_id(name).map(|id| id.unversioned()))
+            .collect::<rfc_dep_ietf::error::Result<Vec<_>>>()?
+    };
+
+    for id in ids.iter().filter(|id| !cache.has_id(id)) {
+        eprintln!("{} is not in the project, import it first", id);
+    }
+
+    let queried = ids.iter().filter(|id| cache.has_id(id)).count();
+    let (changed, errors) = rfc_dep_project::refresh_documents(&mut cache, &ids);
+    for err in &errors {
+        eprintln!("Could not refresh: {}", err);
+    }
+    for id in &changed {
+        println!("{}\tupdated", id);
+    }
+    println!(
+        "{} documents updated, {} unchanged",
+        changed.len(),
+        queried.saturating_sub(changed.len() + errors.len())
+    );
+
+    save_project(&cache, project)?;
+    if !errors.is_empty() {
+        return Err(ProjectError::Doc(format!("could not refresh {} documents", errors.len())));
+    }
+
+    Ok(())
+}
+
+fn revisions(project: &Path) -> Result<()> {
+    let mut cache = load_project(project)?;
+    for err in rfc_dep_project::check_revisions(&mut cache) {
+        eprintln!("Could not check revision: {}", err);
+    }
+
+    for id in rfc_dep_project::outdated_drafts(&cache) {
+        if let Some(state) = cache.get(&id) {
+            println!(
+                "{}\t{} -> {}",
+                id,
+                state.content.summary.revision,
+                state.newer_revision().unwrap_or_default()
+            );
+        }
+    }
+
+    save_project(&cache, project)
+}
+
+fn upgrade(project: &Path, ids: Vec<String>) -> Result<()> {
+    let mut cache = load_project(project)?;
+
+    let ids = if ids.is_empty() {
+        rfc_dep_project::outdated_drafts(&cache)
+    } else {
+        ids.into_iter().map(name_to_id).collect::<rfc_dep_ietf::error::Result<Vec<_>>>()?
+    };
+
+    let mut failed = 0;
+    for id in ids {
+        match rfc_dep_project::select_revision(&mut cache, &id) {
+            Ok(selected) => println!("selected {}", selected),
+            Err(err) => {
+                eprintln!("Could not upgrade {}: {}", id, err);
+                failed += 1;
+            }
+        }
+    }
+
+    save_project(&cache, project)?;
+    if failed > 0 {
+        return Err(ProjectError::Doc(format!("could not upgrade {} documents", failed)));
+    }
+
+    Ok(())
+}
+
+fn history(project: &Path, draft: &str) -> Result<()> {
+    let mut cache = load_project(project)?;
+    let id = name_to_id(draft)?.unversioned();
+    rfc_dep_project::update_revisions(&mut cache, &id)?;
+
+    if let Some(state) = cache.get(&id) {
+        for revision in &state.content.revisions {
+            let selected = revision.revision == state.content.summary.revision;
+            println!(
+                "{}\t{}{}",
+                revision.revision,
+                revision.date.as_deref().unwrap_or("unknown date"),
+                if selected { "\tselected" } else { "" }
+            );
+        }
+    }
+
+    save_project(&cache, project)
+}
+
+fn diff(project: &Path, draft: &str, old: &str, new: Option<String>, context: usize) -> Result<()> {
+    let cache = load_project(project)?;
+    let id = name_to_id(draft)?.unversioned();
+    let state = cache
+        .get(&id)
+        .ok_or_else(|| ProjectError::Doc(format!("{} is not in the project, import it first", id)))?;
+
+    let new = new.unwrap_or_else(|| state.content.summary.revision.clone());
+    let diff = state.content.diff_revisions(old, &new)?;
+    eprintln!("{} -> {}: {} lines deleted, {} lines inserted", old, new, diff.deleted(), diff.inserted());
+    print!("{}", diff.unified(context));
+
+    Ok(())
+}
+
+fn errata(project: &Path, dump: Option<PathBuf>) -> Result<()> {
+    let mut cache = load_project(project)?;
+    let errata = match dump {
+        Some(dump) => load_errata(dump)?,
+        None => fetch_errata()?,
+    };
+    let attached = rfc_dep_project::import_errata(&mut cache, &errata);
+    println!("attached {} errata", attached);
+
+    for (id, state) in &cache {
+        let count = |status| state.content.errata_count(status).unwrap_or_default();
+        if state.content.errata.as_ref().is_some_and(|errata| !errata.is_empty()) {
+            println!(
+                "{}\t{} verified\t{} held\t{} reported\t{} rejected",
+                id,
+                count(ErratumStatus::Verified),
+                count(ErratumStatus::HeldForDocumentUpdate),
+                count(ErratumStatus::Reported),
+                count(ErratumStatus::Rejected)
+            );
+        }
+    }
+
+    save_project(&cache, project)
+}
+
+fn download(project: &Path, ids: Vec<String>, format: DocFormat, output: Option<PathBuf>) -> Result<()> {
+    let mut cache = load_project(project)?;
+
+    let ids = if ids.is_empty() {
+        cache.keys().cloned().collect()
+    } else {
+        ids.into_iter()
+            .map(|name| name_to_id(name).map(|id| id.unversioned()))
+            .collect::<rfc_dep_ietf::error::Result<Vec<_>>>()?
+    };
+
+    if let Some(ref output) = output {
+        fs::create_dir_all(output)?;
+    }
+
+    let errors = rfc_dep_project::download_documents(&mut cache, &ids, format);
+    for err in &errors {
+        eprintln!("Could not download: {}", err);
+    }
+
+    for id in &ids {
+        let Some(state) = cache.get(id) else {
+            eprintln!("{} is not in the project, import it first", id);
+            continue;
+        };
+        let formats: Vec<String> = state.offline.formats().map(|format| format.to_string()).collect();
+        println!("{}\t{}", id, formats.join(", "));
+
+        if let Some(ref output) = output {
+            // the txt copy of documents not published in the format
+            let copy = if state.offline.has(format) { format } else { DocFormat::Text };
+            if let Some(bytes) = state.offline.bytes(copy) {
+                fs::write(output.join(format!("{}.{}", id, copy)), bytes)?;
+            }
+        }
+    }
+
+    save_project(&cache, project)?;
+    if !errors.is_empty() {
+        return Err(ProjectError::Doc(format!("could not download {} documents", errors.len())));
+    }
+
+    Ok(())
+}
+
+fn resolve(project: &Path, ids: Vec<String>, params: ResolveParams) -> Result<()> {
+    let mut cache = load_project(project)?;
+
+    // drafts are cached whatever their revision
+    let ids = ids
+        .into_iter()
+        .map(|id| name_to_id(id).map(|id| id.unversioned()))
+        .collect::<rfc_dep_ietf::error::Result<Vec<_>>>()?;
+    if let Some(id) = ids.iter().find(|id| !cache.has_id(id)) {
+        return Err(ProjectError::Doc(format!(
+            "{} is not in the project, import it first",
+            id
+        )));
+    }
+
+    let target = if ids.is_empty() {
+        ResolveTarget::All
+    } else {
+        ResolveTarget::Multiple(ids)
+    };
+
+    let before = cache.len();
+    rfc_dep_project::resolve(&mut cache, target, params);
+    println!("resolved {} new documents", cache.len() - before);
+
+    save_project(&cache, project)
+}
+
+fn list(project: &Path, missing: bool, equivalent: bool) -> Result<()> {
+    let cache = load_project(project)?;
+    let equivalence = equivalent.then(|| Equivalence::new(&cache));
+    for (id, state) in &cache {
+        let (is_read, relations, missing_deps, others) = match equivalence {
+            Some(ref eq) if !eq.is_representative(id) => continue,
+            Some(ref eq) => {
+                let others: Vec<String> = eq.members(id).iter().skip(1).map(ToString::to_string).collect();
+                let relations = eq.merged_meta(&cache, id).count();
+                (eq.is_read(&cache, id), relations, eq.missing_dep_count(&cache, id), others)
+            }
+            None => (state.is_read, state.content.meta.count(), state.missing_dep_count, Vec::new()),
+        };
+        if missing && missing_deps == 0 {
+            continue;
+        }
+
+        println!(
+            "{}\t{}\t{} relations\t{} unknown\t{}{}",
+            id,
+            if is_read { "read" } else { "unread" },
+            relations,
+            missing_deps,
+            state.content.summary.title,
+            if others.is_empty() { String::new() } else { format!("\t(same as {})", others.join(", ")) }
+        );
+    }
+
+    Ok(())
+}
+
+fn superseded(project: &Path, ids: Vec<String>) -> Result<()> {
+    let cache = load_project(project)?;
+    let print_chain = |id: &DocIdentifier, chain: &[DocIdentifier]| {
+        let chain: Vec<String> = chain.iter().map(ToString::to_string).collect();
+        println!("{} -> {}", id, chain.join(" -> "));
+    };
+
+    if ids.is_empty() {
+        let mut superseded: Vec<_> = rfc_dep_project::superseded_documents(&cache).into_iter().collect();
+        superseded.sort();
+        for (id, chain) in superseded {
+            print_chain(&id, &chain);
+        }
+        return Ok(());
+    }
+
+    for id in ids {
+        let id = name_to_id(id)?.unversioned();
+        if !cache.has_id(&id) {
+            return Err(ProjectError::Doc(format!("{} is not in the project, import it first", id)));
+        }
+
+        match rfc_dep_project::supersession_chain(&cache, &id) {
+            chain if chain.is_empty() => println!("{} is current", id),
+            chain => print_chain(&id, &chain),
+        }
+    }
+
+    Ok(())
+}
+
+fn merge(project: &Path, others: Vec<PathBuf>) -> Result<()> {
+    let mut cache = load_or_default(project)?;
+    for other in others {
+        rfc_dep_project::merge_projects(&mut cache, load_project(other)?);
+    }
+
+    save_project(&cache, project)
+}
+
+fn export(project: &Path, output: Option<PathBuf>) -> Result<()> {
+    let cache = load_project(project)?;
+    match output {
+        Some(output) => save_project(&cache, output),
+        None => {
+            println!("{}", serde_json::to_string_pretty(&cache)?);
+            Ok(())
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let http = match url_config(&cli).and_then(|config| {
+        set_url_config(config);
+        http_source(&cli)
+    }) {
+        Ok(http) => http,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+    let http = RetrySource::new(
+        http,
+        RetryPolicy {
+            max_retries: cli.retries,
+            ..RetryPolicy::default()
+        },
+    )
+    .on_retry(|url, delay, err| eprintln!("Retrying {} in {:?}: {}", url, delay, err));
+    set_rate_limit(Some(cli.rate_limit));
+    set_query_referenced_by(!cli.no_referenced_by);
+
+    if let Some(fixtures) = cli.fixtures {
+        set_default_source(FixtureSource::new(fixtures));
+    } else if let Some(archive) = cli.record {
+        set_default_source(RecordingSource::new(http, archive));
+    } else {
+        set_default_source(http);
+    }
+
+    let result = match cli.command {
+        Command::Lookup {
+            title,
+            filters,
+            limit,
+            include_drafts,
+            json,
+            import,
+        } => lookup(title, filters, limit, include_drafts, json, import),
+        Command::Import { project, names } => import(&project, names),
+        Command::Index { project, index: path } => index(&project, &path),
+        Command::ImportText { project, files } => import_text(&project, files),
+        Command::Repair { project } => repair(&project),
+        Command::Refresh { project, ids } => refresh(&project, ids),
+        Command::Revisions { project } => revisions(&project),
+        Command::Upgrade { project, ids } => upgrade(&project, ids),
+        Command::History { project, draft } => history(&project, &draft),
+        Command::Diff {
+            project,
+            draft,
+            old,
+            new,
+            context,
+        } => diff(&project, &draft, &old, new, context),
+        Command::Errata { project, dump } => errata(&project, dump),
+        Command::Download {
+            project,
+            ids,
+            format,
+            output,
+        } => download(&project, ids, format, output),
+        Command::Resolve {
+            project,
+            ids,
+            depth,
+            no_query,
+            no_references,
+            verbose,
+        } => resolve(
+            &project,
+            ids,
+            ResolveParams {
+                print: verbose,
+                query: !no_query,
+                depth,
+                references: !no_references,
+            },
+        ),
+        Command::List {
+            project,
+            missing,
+            equivalent,
+        } => list(&project, missing, equivalent),
+        Command::Superseded { project, ids } => superseded(&project, ids),
+        Command::Merge { project, others } => merge(&project, others),
+        Command::Export { project, output } => export(&project, output),
+    };
+
+    if let Err(err) = result {
+        eprintln!("Error: {}", err);
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
@@ -1,11 +1,14 @@
 use derivative::Derivative;
 use eframe::egui;
 use egui_notify::Toasts;
+use std::rc::Rc;
 use std::thread::JoinHandle;
 use rfc_dep_cache::{ResolveParams, ResolveTarget};
-use rfc_dep_ietf::{DocIdentifier, Summary};
+use rfc_dep_ietf::{DocDiff, DocFormat, DocIdentifier, Summary};
+use rfc_dep_project::DocCache;
 
-use crate::cache::DocCache;
+use crate::cache::CacheRelations;
+use crate::graph::GraphView;
 use crate::settings::Settings;
 use crate::tabs::Tab;
 
@@ -29,10 +32,22 @@ pub struct RFCDepApp {
     pub(crate) cache_requires_update: bool,
     pub(crate) list_selected_count: usize,
     pub(crate) resolve_handle: Option<JoinHandle<DocCache>>,
+    // derived from the cache, None once it changed
+    pub(crate) relations: Option<Rc<CacheRelations>>,
+    // cached document whose row the table scrolls to (relation links)
+    pub(crate) table_scroll_to: Option<DocIdentifier>,
 
     // RFC Viewer
     pub(crate) selected_tab: Tab,
-    pub(crate) viewed_doc: Option<DocIdentifier>
+    pub(crate) viewed_doc: Option<DocIdentifier>,
+    // revisions of the viewed draft to diff (old, new) and their diff
+    pub(crate) diff_revisions: (String, String),
+    pub(crate) viewed_diff: Option<DocDiff>,
+    // downloaded copy shown, the best available one (see Offline::best_format) when None
+    pub(crate) viewed_format: Option<DocFormat>,
+
+    // Graph View
+    pub(crate) graph: GraphView,
 }
 
 impl RFCDepApp {
@@ -42,6 +57,7 @@ impl RFCDepApp {
 
     pub(crate) fn reset(&mut self) {
         self.cache.clear();
+        self.cache_changed();
         self.list_selected_count = 0;
         self.cache_requires_update = false;
     }
@@ -84,6 +100,7 @@ impl eframe::App for RFCDepApp {
                     print: true,
                     depth: self.settings.max_depth,
                     query: true,
+                    references: self.settings.resolve_references,
                 },
             );
 
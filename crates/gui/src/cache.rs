@@ -1,16 +1,56 @@
 use crate::app::RFCDepApp;
-use crate::doc::{update_missing_dep_count, StatefulDoc};
-use rfc_dep_cache::{Cache, RelationalEntry, ResolveParams, ResolveTarget};
-use rfc_dep_ietf::DocIdentifier;
+use rfc_dep_cache::{ResolveParams, ResolveTarget};
+use rfc_dep_ietf::{DocIdentifier, MetaMap};
+use rfc_dep_project::{DocCache, DocReference, Equivalence};
+use std::collections::HashMap;
+use std::rc::Rc;
 use std::time::Duration;
 use std::{mem, thread};
 
-pub(crate) type DocCache = Cache<DocIdentifier, StatefulDoc>;
+/* Relations derived from the whole cache for the views, too slow to compute on every frame
+ * computed again only once the cache changed, see RFCDepApp::cache_changed */
+#[derive(Debug, Default)]
+pub(crate) struct CacheRelations {
+    // superseded documents and their chain of successors
+    pub(crate) superseded: HashMap<DocIdentifier, Vec<DocIdentifier>>,
+    pub(crate) equivalence: Equivalence,
+    // relations of the members of each group, by representative
+    pub(crate) merged: HashMap<DocIdentifier, MetaMap<DocReference>>,
+}
+
+impl CacheRelations {
+    fn new(cache: &DocCache) -> Self {
+        let equivalence = Equivalence::new(cache);
+        let merged = equivalence
+            .groups()
+            .keys()
+            .map(|head| (head.clone(), equivalence.merged_meta(cache, head)))
+            .collect();
+
+        CacheRelations {
+            superseded: rfc_dep_project::superseded_documents(cache),
+            equivalence,
+            merged,
+        }
+    }
+}
 
 impl RFCDepApp {
+    /* to call after any change of the documents or of their relations */
+    pub(crate) fn cache_changed(&mut self) {
+        self.relations = None;
+    }
+
+    pub(crate) fn relations(&mut self) -> Rc<CacheRelations> {
+        let cache = &self.cache;
+        self.relations
+            .get_or_insert_with(|| Rc::new(CacheRelations::new(cache)))
+            .clone()
+    }
+
     pub(crate) fn merge_caches(&mut self, other: DocCache) {
-        self.cache.merge_with(other);
-        self.update_cache(None, false);
+        rfc_dep_project::merge_projects(&mut self.cache, other);
+        self.cache_changed();
     }
 
     pub(crate) fn update_cache(&mut self, new_cache: Option<DocCache>, recompute: bool) {
@@ -18,13 +58,8 @@ impl RFCDepApp {
             self.cache = new_cache;
         }
 
-        self.cache.update_relations(|_| false, |_, doc, change| {
-            if recompute {
-                doc.missing_dep_count = doc.get_unknown_relations_count();
-            } else {
-                update_missing_dep_count(doc, change)
-            }
-        })
+        rfc_dep_project::update_relations(&mut self.cache, recompute);
+        self.cache_changed();
     }
 
     pub(crate) fn is_resolving(&self) -> bool {
@@ -47,9 +82,10 @@ impl RFCDepApp {
             .info("Resolving...")
             .set_duration(Some(Duration::from_secs(5)));
         let cache = mem::take(&mut self.cache);
+        self.cache_changed();
         self.resolve_handle = Some(thread::spawn(move || {
             let mut cache = cache;
-            cache.resolve_dependencies(target, params, update_missing_dep_count);
+            rfc_dep_project::resolve(&mut cache, target, params);
             cache
         }));
     }
@@ -62,6 +98,7 @@ impl RFCDepApp {
 
         let cache = handle.unwrap().join().unwrap();
         self.cache.merge_with(cache);
+        self.cache_changed();
         self.toasts
             .success("Resolve completed!")
             .set_duration(Some(Duration::from_secs(5)));
@@ -0,0 +1,460 @@
+use derivative::Derivative;
+use eframe::egui;
+use eframe::egui::{Align2, Color32, FontId, Pos2, Rect, Sense, Shape, Stroke, Ui, Vec2};
+use std::collections::{HashMap, HashSet};
+use std::ops::Deref;
+
+use rfc_dep_ietf::{DocIdentifier, Meta};
+use rfc_dep_project::{DocCache, Equivalence};
+
+use crate::app::RFCDepApp;
+
+/* force-directed layout parameters (in graph space units) */
+const REPULSION: f32 = 6000.0;
+const SPRING_LENGTH: f32 = 110.0;
+const SPRING_STIFFNESS: f32 = 0.04;
+const GRAVITY: f32 = 0.01;
+const DAMPING: f32 = 0.85;
+const MAX_SPEED: f32 = 40.0;
+const REST_ENERGY: f32 = 0.5;
+
+const NODE_RADIUS: f32 = 9.0;
+const MIN_ZOOM: f32 = 0.1;
+const MAX_ZOOM: f32 = 5.0;
+
+/* relation kinds drawn as edges, inverse relations (UpdatedBy, ...) are drawn as their forward kind */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum EdgeKind {
+    Updates,
+    Obsoletes,
+    Replaces,
+    Was,
+    AlsoKnownAs,
+    NormativeReference,
+    InformativeReference,
+}
+
+impl EdgeKind {
+    pub(crate) fn all() -> Vec<EdgeKind> {
+        vec![
+            EdgeKind::Updates,
+            EdgeKind::Obsoletes,
+            EdgeKind::Replaces,
+            EdgeKind::Was,
+            EdgeKind::AlsoKnownAs,
+            EdgeKind::NormativeReference,
+            EdgeKind::InformativeReference,
+        ]
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            EdgeKind::Updates => "updates",
+            EdgeKind::Obsoletes => "obsoletes",
+            EdgeKind::Replaces => "replaces",
+            EdgeKind::Was => "was",
+            EdgeKind::AlsoKnownAs => "aka",
+            EdgeKind::NormativeReference => "normative ref",
+            EdgeKind::InformativeReference => "informative ref",
+        }
+    }
+
+    fn color(&self) -> Color32 {
+        match self {
+            EdgeKind::Updates => Color32::from_rgb(80, 150, 255),
+            EdgeKind::Obsoletes => Color32::from_rgb(230, 80, 80),
+            EdgeKind::Replaces => Color32::from_rgb(240, 160, 40),
+            EdgeKind::Was => Color32::from_rgb(150, 150, 150),
+            EdgeKind::AlsoKnownAs => Color32::from_rgb(120, 200, 120),
+            EdgeKind::NormativeReference => Color32::from_rgb(180, 110, 230),
+            EdgeKind::InformativeReference => Color32::from_rgb(200, 170, 230),
+        }
+    }
+
+    fn is_dashed(&self) -> bool {
+        matches!(
+            self,
+            EdgeKind::Was | EdgeKind::AlsoKnownAs | EdgeKind::InformativeReference
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Edge {
+    from: DocIdentifier,
+    to: DocIdentifier,
+    kind: EdgeKind,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    pos: Pos2,
+    velocity: Vec2,
+    pinned: bool,
+}
+
+#[derive(Debug, Derivative)]
+#[derivative(Default)]
+pub(crate) struct GraphView {
+    nodes: HashMap<DocIdentifier, Node>,
+    pan: Vec2,
+    #[derivative(Default(value = "1.0"))]
+    zoom: f32,
+    dragged: Option<DocIdentifier>,
+    #[derivative(Default(value = "true"))]
+    animate: bool,
+}
+
+impl GraphView {
+    /* add nodes for new cache entries, forget nodes of removed entries
+     * equivalent documents are one node, the one of their representative */
+    fn sync_nodes(&mut self, cache: &DocCache, equivalence: Option<&Equivalence>) {
+        let shown = |id: &DocIdentifier| equivalence.is_none_or(|eq| eq.is_representative(id));
+        self.nodes.retain(|id, _| cache.has_id(id) && shown(id));
+
+        let count = cache.len().max(1) as f32;
+        for (idx, (id, _)) in cache.into_iter().enumerate() {
+            if self.nodes.contains_key(id) || !shown(id) {
+                continue;
+            }
+
+            // spread new nodes on a spiral so they don't overlap
+            let angle = idx as f32 * 2.4;
+            let radius = SPRING_LENGTH * (1.0 + idx as f32 / count).sqrt() * 2.0;
+            self.nodes.insert(
+                id.clone(),
+                Node {
+                    pos: Pos2::new(radius * angle.cos(), radius * angle.sin()),
+                    velocity: Vec2::ZERO,
+                    pinned: false,
+                },
+            );
+            self.animate = true;
+        }
+    }
+
+    /* one step of the force-directed layout, returns the total kinetic energy */
+    fn step_layout(&mut self, edges: &[Edge]) -> f32 {
+        let ids: Vec<DocIdentifier> = self.nodes.keys().cloned().collect();
+        let mut forces: HashMap<&DocIdentifier, Vec2> =
+            ids.iter().map(|id| (id, Vec2::ZERO)).collect();
+
+        // nodes repel each other
+        for (i, a) in ids.iter().enumerate() {
+            for b in ids.iter().skip(i + 1) {
+                let delta = self.nodes[a].pos - self.nodes[b].pos;
+                let distance = delta.length().max(1.0);
+                let force = delta / distance * (REPULSION / (distance * distance));
+                *forces.get_mut(a).unwrap() += force;
+                *forces.get_mut(b).unwrap() -= force;
+            }
+        }
+
+        // related nodes attract each other
+        for edge in edges {
+            let delta = self.nodes[&edge.to].pos - self.nodes[&edge.from].pos;
+            let distance = delta.length().max(1.0);
+            let force = delta / distance * ((distance - SPRING_LENGTH) * SPRING_STIFFNESS);
+            *forces.get_mut(&edge.from).unwrap() += force;
+            *forces.get_mut(&edge.to).unwrap() -= force;
+        }
+
+        let mut energy = 0.0;
+        for (id, force) in forces {
+            let node = self.nodes.get_mut(id).unwrap();
+            if node.pinned || self.dragged.as_ref() == Some(id) {
+                node.velocity = Vec2::ZERO;
+                continue;
+            }
+
+            // keep disconnected components around the origin
+            let force = force - node.pos.to_vec2() * GRAVITY;
+            node.velocity = ((node.velocity + force) * DAMPING).clamp(
+                Vec2::splat(-MAX_SPEED),
+                Vec2::splat(MAX_SPEED),
+            );
+            node.pos += node.velocity;
+            energy += node.velocity.length();
+        }
+
+        energy
+    }
+
+    fn to_screen(&self, rect: &Rect, pos: Pos2) -> Pos2 {
+        rect.center() + self.pan + pos.to_vec2() * self.zoom
+    }
+
+    fn to_graph(&self, rect: &Rect, pos: Pos2) -> Pos2 {
+        ((pos - rect.center() - self.pan) / self.zoom).to_pos2()
+    }
+
+    fn node_at(&self, rect: &Rect, pos: Pos2) -> Option<DocIdentifier> {
+        let radius = NODE_RADIUS * self.zoom.max(0.5);
+        self.nodes
+            .iter()
+            .map(|(id, node)| (id, self.to_screen(rect, node.pos).distance(pos)))
+            .filter(|(_, distance)| *distance <= radius)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(id, _)| id.clone())
+    }
+
+    fn reset_view(&mut self) {
+        self.pan = Vec2::ZERO;
+        self.zoom = 1.0;
+    }
+
+    fn relayout(&mut self) {
+        self.nodes.clear();
+        self.animate = true;
+    }
+}
+
+/* list the relations between cached documents, one edge per relation
+ * relations of equivalent documents are the ones of their representative */
+fn collect_edges(cache: &DocCache, equivalence: Option<&Equivalence>) -> Vec<Edge> {
+    let mut edges = HashSet::new();
+
+    let mut add_edge = |from: &DocIdentifier, to: &DocIdentifier, kind: EdgeKind| {
+        let (from, to) = match equivalence {
+            Some(eq) => (&eq.representative(from), &eq.representative(to)),
+            None => (from, to),
+        };
+        if from != to && cache.has_id(from) && cache.has_id(to) {
+            edges.insert(Edge {
+                from: from.clone(),
+                to: to.clone(),
+                kind,
+            });
+        }
+    };
+
+    for (id, state) in cache {
+        for (_, meta) in state.content.meta.deref() {
+            match meta {
+                Meta::Updates(list) => list
+                    .iter()
+                    .for_each(|other| add_edge(id, other, EdgeKind::Updates)),
+                Meta::UpdatedBy(list) => list
+                    .iter()
+                    .for_each(|other| add_edge(other, id, EdgeKind::Updates)),
+                Meta::Obsoletes(list) => list
+                    .iter()
+                    .for_each(|other| add_edge(id, other, EdgeKind::Obsoletes)),
+                Meta::ObsoletedBy(list) => list
+                    .iter()
+                    .for_each(|other| add_edge(other, id, EdgeKind::Obsoletes)),
+                Meta::Replaces(other) => add_edge(id, other, EdgeKind::Replaces),
+                Meta::ReplacedBy(other) => add_edge(other, id, EdgeKind::Replaces),
+                Meta::NormativeReferences(list) => list
+                    .iter()
+                    .for_each(|other| add_edge(id, other, EdgeKind::NormativeReference)),
+                Meta::NormativelyReferencedBy(list) => list
+                    .iter()
+                    .for_each(|other| add_edge(other, id, EdgeKind::NormativeReference)),
+                Meta::InformativeReferences(list) => list
+                    .iter()
+                    .for_each(|other| add_edge(id, other, EdgeKind::InformativeReference)),
+                Meta::InformativelyReferencedBy(list) => list
+                    .iter()
+                    .for_each(|other| add_edge(other, id, EdgeKind::InformativeReference)),
+                Meta::Was(other) => add_edge(id, other, EdgeKind::Was),
+                Meta::AlsoKnownAs(list) => list
+                    .iter()
+                    .for_each(|other| add_edge(id, other, EdgeKind::AlsoKnownAs)),
+                // rfcs are also known as their subseries
+                Meta::Contains(list) => list
+                    .iter()
+                    .for_each(|other| add_edge(other, id, EdgeKind::AlsoKnownAs)),
+            }
+        }
+    }
+
+    edges.into_iter().collect()
+}
+
+fn draw_edge(painter: &egui::Painter, from: Pos2, to: Pos2, kind: EdgeKind, zoom: f32) {
+    let stroke = Stroke::new(1.5, kind.color());
+    let delta = to - from;
+    if delta.length() <= 2.0 * NODE_RADIUS * zoom {
+        return;
+    }
+
+    // stop the edge at the border of the nodes
+    let dir = delta.normalized();
+    let start = from + dir * NODE_RADIUS * zoom;
+    let end = to - dir * NODE_RADIUS * zoom;
+
+    if kind.is_dashed() {
+        painter.extend(Shape::dashed_line(&[start, end], stroke, 6.0, 4.0));
+    } else {
+        painter.line_segment([start, end], stroke);
+    }
+
+    // arrow head
+    let head = 8.0 * zoom.clamp(0.5, 1.5);
+    let rot = egui::emath::Rot2::from_angle(0.5);
+    painter.line_segment([end, end - rot * dir * head], stroke);
+    painter.line_segment([end, end - rot.inverse() * dir * head], stroke);
+}
+
+impl RFCDepApp {
+    pub(crate) fn make_graph_view(&mut self, ui: &mut Ui) {
+        let relations = self.relations();
+        let graph = &mut self.graph;
+        let cache = &mut self.cache;
+        let equivalence = self.settings.equivalence.then_some(&relations.equivalence);
+
+        graph.sync_nodes(cache, equivalence);
+        let edges = collect_edges(cache, equivalence);
+
+        // Legend and view controls
+        ui.horizontal(|ui| {
+            for kind in EdgeKind::all() {
+                ui.colored_label(kind.color(), format!("— {}", kind.name()));
+            }
+            ui.separator();
+            ui.checkbox(&mut graph.animate, "animate");
+            if ui.button("relayout").clicked() {
+                graph.relayout();
+                graph.sync_nodes(cache, equivalence);
+            }
+            if ui.button("reset view").clicked() {
+                graph.reset_view();
+            }
+        });
+
+        let (response, painter) = ui.allocate_painter(ui.available_size(), Sense::click_and_drag());
+        let rect = response.rect;
+
+        if cache.is_empty() {
+            painter.text(
+                rect.center(),
+                Align2::CENTER_CENTER,
+                "Include documents to see their relations here.",
+                FontId::proportional(14.0),
+                ui.visuals().weak_text_color(),
+            );
+            return;
+        }
+
+        // Zoom around the pointer
+        if let Some(pointer) = response.hover_pos() {
+            let (scroll, zoom) = ui.input(|i| (i.scroll_delta.y, i.zoom_delta()));
+            let factor = zoom * (scroll / 200.0).exp();
+            if factor != 1.0 {
+                let anchor = graph.to_graph(&rect, pointer);
+                graph.zoom = (graph.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+                graph.pan = pointer - rect.center() - anchor.to_vec2() * graph.zoom;
+            }
+        }
+
+        // Drag a node to move it (and pin it), drag the background to pan
+        if response.drag_started() {
+            graph.dragged = response
+                .interact_pointer_pos()
+                .and_then(|pos| graph.node_at(&rect, pos));
+        }
+        if response.dragged() {
+            let delta = response.drag_delta();
+            if let Some(id) = graph.dragged.clone() {
+                if let Some(node) = graph.nodes.get_mut(&id) {
+                    node.pos += delta / graph.zoom;
+                    node.pinned = true;
+                }
+            } else {
+                graph.pan += delta;
+            }
+        }
+        if response.drag_released() {
+            graph.dragged = None;
+        }
+
+        // Click to (de)select, right click to unpin
+        // (the first click of a double click would already have changed the selection)
+        if let Some(id) = response
+            .interact_pointer_pos()
+            .filter(|_| response.clicked() || response.secondary_clicked())
+            .and_then(|pos| graph.node_at(&rect, pos))
+        {
+            if response.secondary_clicked() {
+                if let Some(node) = graph.nodes.get_mut(&id) {
+                    node.pinned = false;
+                }
+            } else if let Some(state) = cache.get_mut(&id) {
+                state.is_selected = !state.is_selected;
+                if state.is_selected {
+                    self.list_selected_count += 1
+                } else {
+                    self.list_selected_count -= 1
+                }
+            }
+        }
+
+        if graph.animate || graph.dragged.is_some() {
+            let energy = graph.step_layout(&edges);
+            if energy > REST_ENERGY || graph.dragged.is_some() {
+                ui.ctx().request_repaint();
+            } else {
+                graph.animate = false;
+            }
+        }
+
+        let painter = painter.with_clip_rect(rect);
+
+        for edge in &edges {
+            let from = graph.to_screen(&rect, graph.nodes[&edge.from].pos);
+            let to = graph.to_screen(&rect, graph.nodes[&edge.to].pos);
+            draw_edge(&painter, from, to, edge.kind, graph.zoom);
+        }
+
+        let hovered = response.hover_pos().and_then(|pos| graph.node_at(&rect, pos));
+        let visuals = ui.visuals();
+        for (id, state) in &*cache {
+            let Some(node) = graph.nodes.get(id) else {
+                continue;
+            };
+            let center = graph.to_screen(&rect, node.pos);
+
+            let is_read = equivalence.map_or(state.is_read, |eq| eq.is_read(cache, id));
+            let fill = if is_read {
+                Color32::from_rgb(90, 170, 90)
+            } else if state.content.summary.is_rfc {
+                visuals.widgets.inactive.bg_fill
+            } else {
+                visuals.faint_bg_color
+            };
+            let stroke = if state.is_selected {
+                Stroke::new(3.0, visuals.selection.stroke.color)
+            } else if hovered.as_ref() == Some(id) {
+                visuals.widgets.hovered.fg_stroke
+            } else {
+                visuals.widgets.inactive.fg_stroke
+            };
+
+            painter.circle(center, NODE_RADIUS * graph.zoom, fill, stroke);
+            if graph.zoom >= 0.4 {
+                painter.text(
+                    center + Vec2::new(0.0, (NODE_RADIUS + 2.0) * graph.zoom),
+                    Align2::CENTER_TOP,
+                    id,
+                    FontId::proportional(12.0),
+                    visuals.text_color(),
+                );
+            }
+        }
+
+        if let Some(state) = hovered.as_ref().and_then(|id| cache.get(id)) {
+            let mut text = format!(
+                "{}\n{}\n{} relations",
+                state.content.summary.id,
+                state.content.summary.title,
+                state.content.meta.count()
+            );
+            if let Some(group) = equivalence.and_then(|eq| eq.groups().get(&state.id())) {
+                let others = group.iter().skip(1).map(ToString::to_string).collect::<Vec<_>>();
+                text.push_str(&format!("\nsame document as {}", others.join(", ")));
+            }
+            response.on_hover_text(text);
+        }
+    }
+}
@@ -1,8 +1,13 @@
 use crate::app::RFCDepApp;
+use rfc_dep_ietf::{
+    set_default_source, set_url_config, HttpSource, RecordingSource, ReplaySource, RetryPolicy, RetrySource,
+    UrlConfig,
+};
+use std::env;
 
 mod app;
 mod cache;
-mod doc;
+mod graph;
 mod menubar;
 mod settings;
 mod sidebar;
@@ -10,8 +15,35 @@ mod table_view;
 mod tabs;
 mod viewer;
 
-// TODO support datatracker -> refererences/referencedby (https://datatracker.ietf.org/doc/draft-raszuk-idr-flow-spec-v6/)
 fn main() {
+    // Mirrors of the datatracker and of the rfc / draft archives (urls or local directories)
+    let mut urls = UrlConfig::default();
+    for (base, var) in [
+        (&mut urls.datatracker, "RFC_DEP_DATATRACKER_URL"),
+        (&mut urls.rfc_archive, "RFC_DEP_RFC_URL"),
+        (&mut urls.draft_archive, "RFC_DEP_DRAFT_URL"),
+        (&mut urls.errata, "RFC_DEP_ERRATA_URL"),
+    ] {
+        if let Ok(url) = env::var(var) {
+            match UrlConfig::parse_base(&url) {
+                Ok(url) => *base = url,
+                Err(err) => eprintln!("Ignoring {}: {}", var, err),
+            }
+        }
+    }
+    set_url_config(urls);
+
+    // Record / Replay datatracker traffic to / from an archive directory
+    if let Ok(archive) = env::var("RFC_DEP_REPLAY") {
+        set_default_source(ReplaySource::new(archive));
+    } else if let Ok(archive) = env::var("RFC_DEP_RECORD") {
+        set_default_source(RecordingSource::new(HttpSource::default(), archive));
+    } else {
+        set_default_source(
+            RetrySource::new(HttpSource::default(), RetryPolicy::default())
+                .on_retry(|url, delay, err| eprintln!("Retrying {} in {:?}: {}", url, delay, err)),
+        );
+    }
 
     let options = eframe::NativeOptions {
         centered: true,
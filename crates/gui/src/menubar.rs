@@ -2,18 +2,43 @@ use eframe::egui;
 use eframe::egui::{Context, Ui};
 use egui_modal::Modal;
 use if_chain::if_chain;
-use std::fs::File;
 use std::time::Duration;
-use rayon::prelude::*;
 
 use rfc_dep_cache::{ResolveParams, ResolveTarget};
-use rfc_dep_ietf::IetfDoc;
+use rfc_dep_ietf::error::Result;
+use rfc_dep_ietf::{fetch_errata, load_errata, DocIdentifier, ErrataMap};
+use rfc_dep_project::{
+    check_revisions, download_documents, import_errata, import_rfc_index, import_text, load_project, outdated_drafts,
+    refresh_documents, repair_from_texts, save_project,
+};
 
 use crate::app::RFCDepApp;
-use crate::cache::DocCache;
-use crate::doc::StatefulDoc;
 
 impl RFCDepApp {
+    pub(crate) fn refresh(&mut self, ids: &[DocIdentifier]) {
+        let (changed, errors) = refresh_documents(&mut self.cache, ids);
+        self.cache_changed();
+        for err in errors {
+            self.toasts.error(err).set_duration(Some(Duration::from_secs(5)));
+        }
+        self.toasts.info(format!("Updated {} of {} documents", changed.len(), ids.len()))
+            .set_duration(Some(Duration::from_secs(5)));
+    }
+
+    fn attach_errata(&mut self, errata: Result<ErrataMap>) {
+        match errata {
+            Ok(errata) => {
+                let attached = import_errata(&mut self.cache, &errata);
+                self.cache_changed();
+                self.toasts.info(format!("Attached {} errata", attached))
+                    .set_duration(Some(Duration::from_secs(5)));
+            }
+            Err(err) => {
+                self.toasts.error(err).set_duration(Some(Duration::from_secs(5)));
+            }
+        }
+    }
+
     pub(crate) fn make_menu(&mut self, ui: &mut Ui, confirm_clear: Modal, import_name: Modal) {
         egui::menu::bar(ui, |ui| {
 
@@ -24,11 +49,13 @@ impl RFCDepApp {
                     if let Some(path) = rfd::FileDialog::new()
                         .add_filter("json", &["json"])
                         .pick_file();
-                    if let Ok(file) = File::open(path);
                     then {
-                        let new_cache = serde_json::from_reader(file).unwrap();
-                        println!("{:#?}", new_cache);
-                        self.update_cache(Some(new_cache), true);
+                        match load_project(path) {
+                            Ok(new_cache) => self.update_cache(Some(new_cache), false),
+                            Err(err) => {
+                                self.toasts.error(err).set_duration(Some(Duration::from_secs(5)));
+                            }
+                        }
                     }
                 }
 
@@ -38,9 +65,9 @@ impl RFCDepApp {
                     if let Some(path) = rfd::FileDialog::new()
                         .add_filter("json", &["json"])
                         .save_file();
-                    if let Ok(file) = &File::create(path);
+                    if let Err(err) = save_project(&self.cache, path);
                     then {
-                        serde_json::to_writer_pretty(file, &self.cache).unwrap();
+                        self.toasts.error(err).set_duration(Some(Duration::from_secs(5)));
                     }
                 }
 
@@ -50,12 +77,33 @@ impl RFCDepApp {
                     if let Some(path) = rfd::FileDialog::new()
                         .add_filter("json", &["json"])
                         .pick_file();
-                    if let Ok(file) = File::open(path);
                     then {
-                        let new_state: DocCache = serde_json::from_reader(file).unwrap();
-                        println!("{:#?}", new_state);
-                        self.merge_caches(new_state);
-                        println!("{:#?}", self.cache);
+                        match load_project(path) {
+                            Ok(new_state) => self.merge_caches(new_state),
+                            Err(err) => {
+                                self.toasts.error(err).set_duration(Some(Duration::from_secs(5)));
+                            }
+                        }
+                    }
+                }
+
+                // Import RFC Index Button
+                if_chain! {
+                    if ui.button("Import RFC Index").clicked();
+                    if let Some(path) = rfd::FileDialog::new()
+                        .add_filter("xml", &["xml"])
+                        .pick_file();
+                    then {
+                        match import_rfc_index(&mut self.cache, path) {
+                            Ok(added) => {
+                                self.cache_changed();
+                                self.toasts.info(format!("Imported {} documents", added))
+                                    .set_duration(Some(Duration::from_secs(5)));
+                            }
+                            Err(err) => {
+                                self.toasts.error(err).set_duration(Some(Duration::from_secs(5)));
+                            }
+                        }
                     }
                 }
 
@@ -70,6 +118,68 @@ impl RFCDepApp {
                 if ui.button("From name").clicked() {
                     import_name.open()
                 }
+
+                // Import from downloaded text
+                if_chain! {
+                    if ui.button("From text file").clicked();
+                    if let Some(path) = rfd::FileDialog::new()
+                        .add_filter("txt", &["txt"])
+                        .pick_file();
+                    then {
+                        let imported = std::fs::read_to_string(path)
+                            .map_err(|err| err.to_string())
+                            .and_then(|text| import_text(&mut self.cache, text).map_err(String::from));
+                        self.cache_changed();
+                        if let Err(err) = imported {
+                            self.toasts.error(err).set_duration(Some(Duration::from_secs(5)));
+                        }
+                    }
+                }
+
+                ui.separator();
+
+                if ui.button("Repair from texts").clicked() {
+                    let repaired = repair_from_texts(&mut self.cache);
+                    self.cache_changed();
+                    self.toasts.info(format!("Repaired {} documents", repaired))
+                        .set_duration(Some(Duration::from_secs(5)));
+                }
+
+                if ui.button("Check new revisions").clicked() {
+                    for err in check_revisions(&mut self.cache) {
+                        self.toasts.error(err).set_duration(Some(Duration::from_secs(5)));
+                    }
+                    self.toasts.info(format!("{} drafts have a newer revision", outdated_drafts(&self.cache).len()))
+                        .set_duration(Some(Duration::from_secs(5)));
+                }
+
+                if ui.button("Refresh documents")
+                    .on_hover_text("query again the documents (the selected ones if any) whose pages changed")
+                    .clicked() {
+                    let selected = self.list_selected_count > 0;
+                    let ids: Vec<DocIdentifier> = self.cache.into_iter()
+                        .filter(|(_, v)| !selected || v.is_selected)
+                        .map(|(id, _)| id.clone())
+                        .collect();
+                    self.refresh(&ids);
+                }
+
+                ui.separator();
+
+                // Errata of the rfcs, from the rfc-editor or a local copy of its dump
+                if ui.button("Fetch errata").clicked() {
+                    self.attach_errata(fetch_errata());
+                }
+
+                if_chain! {
+                    if ui.button("Load errata dump").clicked();
+                    if let Some(path) = rfd::FileDialog::new()
+                        .add_filter("json", &["json"])
+                        .pick_file();
+                    then {
+                        self.attach_errata(load_errata(path));
+                    }
+                }
             });
 
             let cache_size = self.cache.len();
@@ -97,9 +207,11 @@ impl RFCDepApp {
 
                     ui.add_enabled_ui(self.list_selected_count > 0, |ui| {
                         if ui.button("Download selected").clicked() {
-                            let errs: Vec<_> = (&mut self.cache).into_iter().par_bridge().filter_map(|(_, v)| {
-                                v.download().err()
-                            }).collect();
+                            let selected: Vec<DocIdentifier> = self.cache.into_iter()
+                                .filter(|(_, v)| v.is_selected)
+                                .map(|(id, _)| id.clone())
+                                .collect();
+                            let errs = download_documents(&mut self.cache, &selected, self.settings.download_format);
 
                             errs.iter().for_each(|err| {
                                 self.toasts.error(err.to_string())
@@ -110,8 +222,8 @@ impl RFCDepApp {
 
                     ui.add_enabled_ui(self.list_selected_count > 0, |ui| {
                         if ui.button("Forget selected").clicked() {
-                            (&mut self.cache).into_iter().for_each(|(_, v)| {
-                                v.offline = None
+                            (&mut self.cache).into_iter().filter(|(_, v)| v.is_selected).for_each(|(_, v)| {
+                                v.offline.clear()
                             });
                         }
                     });
@@ -128,6 +240,11 @@ impl RFCDepApp {
                 });
             });
 
+            ui.menu_button("View", |ui| {
+                ui.checkbox(&mut self.settings.equivalence, "Merge equivalent documents")
+                    .on_hover_text("show an rfc, the drafts it was published from and its aliases as one document");
+            });
+
             // update value since cache may have been updated
             let cache_size = self.cache.len();
             ui.add_enabled_ui(cache_size > 0, |ui| {
@@ -150,6 +267,7 @@ impl RFCDepApp {
                                 print: true,
                                 depth: self.settings.max_depth,
                                 query: true,
+                                references: self.settings.resolve_references,
                             },
                         );
                     }
@@ -207,16 +325,14 @@ impl RFCDepApp {
                     if self.direct_import_name.is_empty() {
                         return;
                     }
-                    let doc = IetfDoc::from_name(&self.direct_import_name);
-                    if let Ok(doc) = doc {
-                        self.cache
-                            .cache(doc.summary.id.clone(), StatefulDoc::new(doc));
-                    } else {
+                    let result = rfc_dep_project::import_name(&mut self.cache, &self.direct_import_name);
+                    self.cache_changed();
+                    if let Err(err) = result {
                         self.toasts
                             .error(format!(
                                 "Could not import {}: {}",
                                 &self.direct_import_name,
-                                doc.err().unwrap()
+                                err
                             ))
                             .set_closable(true)
                             .set_duration(Some(Duration::from_secs(10)));
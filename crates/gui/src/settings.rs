@@ -1,6 +1,7 @@
 use crate::app::RFCDepApp;
 use derivative::Derivative;
-use eframe::egui::{popup, DragValue, Ui};
+use eframe::egui::{popup, ComboBox, DragValue, Ui};
+use rfc_dep_ietf::{set_query_referenced_by, DocFormat};
 
 #[derive(Debug, Derivative)]
 #[derivative(Default)]
@@ -9,13 +10,28 @@ pub(crate) struct QuerySettings {
     pub(crate) limit: usize,
 
     pub(crate) include_drafts: bool,
+
+    // query the documents citing the queried ones, see rfc_dep_ietf::set_query_referenced_by
+    #[derivative(Default(value = "true"))]
+    pub(crate) referenced_by: bool,
 }
 
-#[derive(Default, Debug)]
+#[derive(Debug, Derivative)]
+#[derivative(Default)]
 pub(crate) struct Settings {
     pub(crate) query: QuerySettings,
 
     pub(crate) max_depth: usize,
+
+    // query the normative / informative references when resolving, not only the other relations
+    #[derivative(Default(value = "true"))]
+    pub(crate) resolve_references: bool,
+
+    // format documents are downloaded in, as text when they aren't published in it
+    pub(crate) download_format: DocFormat,
+
+    // show equivalent documents (an rfc and its drafts) as one, see rfc_dep_project::Equivalence
+    pub(crate) equivalence: bool,
 }
 
 impl RFCDepApp {
@@ -36,6 +52,29 @@ impl RFCDepApp {
                     ui.label("include drafts");
                     ui.checkbox(&mut self.settings.query.include_drafts, "");
                 });
+
+                ui.horizontal(|ui| {
+                    ui.label("query referenced by");
+                    if ui.checkbox(&mut self.settings.query.referenced_by, "").changed() {
+                        set_query_referenced_by(self.settings.query.referenced_by);
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("resolve references");
+                    ui.checkbox(&mut self.settings.resolve_references, "");
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("download format");
+                    ComboBox::from_id_source("download_format")
+                        .selected_text(self.settings.download_format.to_string())
+                        .show_ui(ui, |ui| {
+                            for format in DocFormat::ALL {
+                                ui.selectable_value(&mut self.settings.download_format, format, format.to_string());
+                            }
+                        });
+                });
             });
         });
 
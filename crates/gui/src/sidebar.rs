@@ -1,12 +1,11 @@
 use eframe::egui;
 use eframe::egui::{Align, TextEdit, Ui};
-use rayon::prelude::*;
 use std::time::Duration;
 
 use rfc_dep_ietf::IetfDoc;
+use rfc_dep_project::DocReference;
 
 use crate::app::RFCDepApp;
-use crate::doc::{DocReference, StatefulDoc};
 
 impl RFCDepApp {
     pub(crate) fn query_docs(&mut self) {
@@ -59,28 +58,20 @@ impl RFCDepApp {
 
                     if ui.button("include").clicked() {
                         let selected = &self.selected_query_docs;
-                        let mut results: Vec<_> = selected
+                        let results: Vec<_> = selected
                             .iter()
                             .enumerate()
                             .filter_map(|(i, b)| if *b { Some(i) } else { None })
                             .map(|i| self.query_result.get(i).unwrap().clone())
                             .collect();
 
-                        let mut results: Vec<_> = results
-                            .par_drain(..)
-                            .filter_map(|summary| {
-                                if let Ok(doc) = IetfDoc::from_summary(summary) {
-                                    Some(doc)
-                                } else {
-                                    None
-                                }
-                            })
-                            .collect();
-
-                        results.drain(..).for_each(|doc| {
-                            self.cache
-                                .cache(doc.summary.id.clone(), StatefulDoc::new(doc));
-                        });
+                        rfc_dep_project::import_summaries(&mut self.cache, results)
+                            .iter()
+                            .for_each(|err| {
+                                self.toasts.error(err.to_string())
+                                    .set_duration(Some(Duration::from_secs(5)));
+                            });
+                        self.cache_changed();
                     }
                 });
 
@@ -96,7 +87,7 @@ impl RFCDepApp {
                                 self.query_result.iter().enumerate().filter(|(idx, val)| {
                                     range.contains(idx)
                                         && (val.title.contains(self.query_filter.as_str())
-                                            || val.id.contains(self.query_filter.as_str()))
+                                            || val.id.to_string().contains(self.query_filter.as_str()))
                                 });
 
                             for (idx, doc) in displayed {
@@ -106,7 +97,9 @@ impl RFCDepApp {
                                     &doc.title,
                                 );
                                 ui.label(format!("{}, rev {}", &doc.id, doc.revision));
-                                ui.hyperlink_to("datatracker", doc.url.html());
+                                if let Ok(url) = doc.url.html() {
+                                    ui.hyperlink_to("datatracker", url);
+                                }
                             }
                             ui.separator();
                         },
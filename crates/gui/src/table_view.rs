@@ -1,50 +1,119 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::ops::Deref;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use crate::app::RFCDepApp;
-use crate::doc::{DocReference, StatefulDoc};
-use eframe::egui::{Id, popup, Response, Ui};
+use eframe::egui::{Align, Id, popup, Response, Ui};
 use egui_extras::{Column, TableBuilder};
 use rfc_dep_cache::CacheReference;
-use rfc_dep_ietf::{DocIdentifier, IetfDoc, Meta};
+use rfc_dep_ietf::{DocIdentifier, Erratum, ErratumStatus, IetfDoc, Meta, Refresh};
+use rfc_dep_project::{DocReference, StatefulDoc};
 
-fn name_to_href(ui: &mut Ui, s: &String) -> Response {
-    ui.hyperlink_to(s, IetfDoc::<DocReference>::id_to_url(s).unwrap().html())
+fn name_to_href(ui: &mut Ui, id: &DocIdentifier) -> Response {
+    match IetfDoc::<DocReference>::id_to_url(id).and_then(|url| url.html()) {
+        Ok(url) => ui.hyperlink_to(id.to_string(), url),
+        Err(_) => ui.label(id.to_string()),
+    }
 }
 
-fn list_meta_links(ui: &mut Ui, list: &HashSet<DocReference>) {
-    for DocReference(meta) in list {
-        match meta {
-            CacheReference::Unknown(id) => {
-                name_to_href(ui, id);
-            }
-            CacheReference::Cached(id) => {
-                name_to_href(ui, id);
+/* one line per erratum: "#5 Verified, Technical, section 3.1" */
+pub(crate) fn erratum_label(erratum: &Erratum) -> String {
+    let mut label = format!("#{} {:?}, {:?}", erratum.id, erratum.status, erratum.kind);
+    if let Some(ref section) = erratum.section {
+        label.push_str(&format!(", section {}", section));
+    }
+
+    label
+}
+
+/* time since a document was queried: "3 days ago", "never" for documents from an index or a text */
+fn fetched_label(fetched_at: Option<u64>) -> String {
+    let Some(fetched_at) = fetched_at else {
+        return "never".to_string();
+    };
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |now| now.as_secs());
+
+    match now.saturating_sub(fetched_at) {
+        elapsed if elapsed < 60 => "just now".to_string(),
+        elapsed if elapsed < 3600 => format!("{} min ago", elapsed / 60),
+        elapsed if elapsed < 86400 => format!("{} h ago", elapsed / 3600),
+        elapsed => format!("{} days ago", elapsed / 86400),
+    }
+}
+
+/* link of a relation: the row of a cached document, the datatracker page otherwise */
+fn reference_link(ui: &mut Ui, DocReference(meta): &DocReference, scroll_to: &mut Option<DocIdentifier>) {
+    match meta {
+        CacheReference::Unknown(id) => {
+            name_to_href(ui, id);
+        }
+        CacheReference::Cached(id) => {
+            if ui.link(id.to_string()).on_hover_text("show in table").clicked() {
+                *scroll_to = Some(id.clone());
             }
         }
     }
 }
 
+fn list_meta_links(ui: &mut Ui, list: &HashSet<DocReference>, scroll_to: &mut Option<DocIdentifier>) {
+    for reference in list {
+        reference_link(ui, reference, scroll_to);
+    }
+}
+
 impl RFCDepApp {
     pub(crate) fn make_table_view(&mut self, ui: &mut Ui) {
         let action_popup = ui.make_persistent_id("table_item_actions");
+        let relations = self.relations();
+        let superseded = &relations.superseded;
+        // equivalent documents are one row, the one of their representative, with their merged relations
+        let equivalence = self.settings.equivalence.then_some(&relations.equivalence);
+        let shown = |id: &DocIdentifier| equivalence.is_none_or(|eq| eq.is_representative(id));
+        let no_merge = HashMap::new();
+        let merged = if equivalence.is_some() { &relations.merged } else { &no_merge };
+        let mut shared_read = HashMap::new();
+        if let Some(eq) = equivalence {
+            for head in eq.groups().keys() {
+                shared_read.insert(head.clone(), eq.is_read(&self.cache, head));
+            }
+        }
+
+        // row of the document of the relation clicked on the previous frame
+        let scroll_row = self.table_scroll_to.take().and_then(|id| {
+            let id = equivalence.map_or(id.clone(), |eq| eq.representative(&id));
+            self.cache.keys().filter(|key| shown(key)).position(|key| *key == id)
+        });
+        let mut scroll_to = None;
+        let mut read_changed = None;
 
-        TableBuilder::new(ui)
+        let mut table = TableBuilder::new(ui)
             .striped(true)
-            .vscroll(true)
+            .vscroll(true);
+        if let Some(row) = scroll_row {
+            table = table.scroll_to_row(row, Some(Align::Center));
+        }
+
+        table
             .column(Column::initial(20.0).clip(false).resizable(true)) //
             .column(Column::initial(60.0).clip(false).resizable(true)) // Actions
             .column(Column::initial(30.0).clip(false).resizable(true)) // Read
             .column(Column::initial(50.0).clip(true).resizable(true)) // Name
             .column(Column::initial(160.0).clip(true).resizable(true)) // Title
+            .column(Column::initial(90.0).clip(true).resizable(true)) // Status
+            .column(Column::initial(40.0).clip(true).resizable(true)) // Stream
+            .column(Column::initial(50.0).clip(true).resizable(true)) // Group
+            .column(Column::initial(70.0).clip(true).resizable(true)) // Date
             .column(Column::initial(50.0).clip(true).resizable(true)) // Relations
+            .column(Column::initial(50.0).clip(true).resizable(true)) // Errata
             .column(Column::initial(30.0).clip(true).resizable(true)) // AKA
-            .columns(Column::initial(75.0).clip(true).resizable(true), 6) // Was
+            .columns(Column::initial(75.0).clip(true).resizable(true), 9) // Was
             // Replaces
             // Updates
             // Obsoletes
             // Updated By
             // Obsoleted By
+            // Normative Refs
+            // Informative Refs
+            // Referenced By
             .header(10.0, |mut header| {
                 vec![
                     "",
@@ -52,7 +121,12 @@ impl RFCDepApp {
                     "Read",
                     "Name",
                     "Title",
+                    "Status",
+                    "Stream",
+                    "Group",
+                    "Date",
                     "Relations",
+                    "Errata",
                     "AKA",
                     "Was",
                     "Replaces",
@@ -60,6 +134,9 @@ impl RFCDepApp {
                     "Obsoletes",
                     "Updated By",
                     "Obsoleted By",
+                    "Normative Refs",
+                    "Informative Refs",
+                    "Referenced By",
                 ]
                     .drain(..)
                     .for_each(|x| {
@@ -70,6 +147,10 @@ impl RFCDepApp {
             })
             .body(|mut body| {
                 for (id, state) in (&mut self.cache).into_iter() {
+                    if !shown(id) {
+                        continue;
+                    }
+
                     body.row(30.0, |mut row| {
                         row.col(|ui| {
                             if ui.checkbox(&mut state.is_selected, "").clicked() {
@@ -93,84 +174,203 @@ impl RFCDepApp {
                         });
 
                         let doc = &state.content;
+                        let meta = merged.get(id).unwrap_or(&doc.meta);
+                        row.col(|ui| {
+                            // shared by equivalent documents
+                            let mut is_read = shared_read.get(id).copied().unwrap_or(state.is_read);
+                            if ui.horizontal_centered(|ui| ui.checkbox(&mut is_read, "")).inner.changed() {
+                                state.is_read = is_read;
+                                read_changed = Some(id.clone());
+                            }
+                        });
+                        row.col(|ui| {
+                            ui.horizontal(|ui| {
+                                // drafts are shown with their selected revision
+                                name_to_href(ui, &doc.summary.id);
+                                if let Some(group) = equivalence.and_then(|eq| eq.groups().get(id)) {
+                                    let others = group.iter().skip(1).map(ToString::to_string).collect::<Vec<_>>();
+                                    ui.label(format!("≡{}", others.len()))
+                                        .on_hover_text(format!("same document as {}", others.join(", ")));
+                                }
+                                if let Some(latest) = state.newer_revision() {
+                                    ui.label(format!("⬆{}", latest))
+                                        .on_hover_text("newer revision on the datatracker, upgrade it from the actions");
+                                }
+                                // link to the current document of the chain
+                                if let Some((chain, head)) = superseded.get(id).and_then(|chain| Some((chain, chain.last()?))) {
+                                    let chain = chain.iter().map(ToString::to_string).collect::<Vec<_>>();
+                                    ui.label("⚠→").on_hover_text(format!("superseded by {}", chain.join(" → ")));
+                                    name_to_href(ui, head);
+                                }
+                            });
+                        });
+                        row.col(|ui| {
+                            let summary = &doc.summary;
+                            let title = ui.label(summary.title.clone());
+                            if !summary.authors.is_empty() || summary.abstract_text.is_some() {
+                                title.on_hover_ui(|ui| {
+                                    ui.set_max_width(400.0);
+                                    if !summary.authors.is_empty() {
+                                        ui.label(summary.authors.join(", "));
+                                    }
+                                    if let Some(pages) = summary.pages {
+                                        ui.label(format!("{} pages", pages));
+                                    }
+                                    if let Some(ref abstract_text) = summary.abstract_text {
+                                        ui.separator();
+                                        ui.label(abstract_text);
+                                    }
+                                });
+                            }
+                        });
+                        row.col(|ui| {
+                            ui.label(doc.summary.status.clone().unwrap_or_default());
+                        });
                         row.col(|ui| {
-                            ui.horizontal_centered(|ui| ui.checkbox(&mut state.is_read, ""));
+                            ui.label(doc.summary.stream.clone().unwrap_or_default());
                         });
                         row.col(|ui| {
-                            name_to_href(ui, id);
+                            ui.label(doc.summary.group.clone().unwrap_or_default());
                         });
                         row.col(|ui| {
-                            ui.label(doc.summary.title.clone());
+                            ui.label(doc.summary.date.clone().unwrap_or_default());
                         });
                         row.col(|ui| {
-                            ui.label(doc.meta.count().to_string());
+                            ui.label(meta.count().to_string());
+                        });
+                        row.col(|ui| {
+                            // verified / held for document update, empty until the errata are queried
+                            let (Some(verified), Some(held)) = (
+                                doc.errata_count(ErratumStatus::Verified),
+                                doc.errata_count(ErratumStatus::HeldForDocumentUpdate),
+                            ) else {
+                                return;
+                            };
+                            let label = ui.label(format!("{verified} V / {held} H"));
+                            if let Some(errata) = doc.errata.as_ref().filter(|errata| !errata.is_empty()) {
+                                label.on_hover_text(errata.iter().map(erratum_label).collect::<Vec<_>>().join("\n"));
+                            }
                         });
                         row.col(|ui| {
                             ui.horizontal(|ui| {
-                                for (_, meta) in doc.meta.deref() {
-                                    if let Meta::AlsoKnownAs(id) = meta {
-                                        name_to_href(ui, id);
+                                // subseries of an rfc, rfcs of a subseries
+                                for (_, meta) in meta.deref() {
+                                    if let Meta::AlsoKnownAs(list) | Meta::Contains(list) = meta {
+                                        list_meta_links(ui, list, &mut scroll_to);
                                     }
                                 }
                             });
                         });
                         row.col(|ui| {
                             ui.horizontal(|ui| {
-                                for (_, meta) in doc.meta.deref() {
-                                    if let Meta::Was(id) = meta {
-                                        name_to_href(ui, id);
+                                for (_, meta) in meta.deref() {
+                                    if let Meta::Was(reference) = meta {
+                                        reference_link(ui, reference, &mut scroll_to);
                                     }
                                 }
                             });
                         });
                         row.col(|ui| {
                             ui.horizontal(|ui| {
-                                for (_, meta) in doc.meta.deref() {
-                                    if let Meta::Replaces(id) = meta {
-                                        name_to_href(ui, id);
+                                for (_, meta) in meta.deref() {
+                                    if let Meta::Replaces(reference) = meta {
+                                        reference_link(ui, reference, &mut scroll_to);
                                     }
                                 }
                             });
                         });
                         row.col(|ui| {
                             ui.horizontal(|ui| {
-                                for (_, meta) in doc.meta.deref() {
+                                for (_, meta) in meta.deref() {
                                     if let Meta::Updates(list) = meta {
-                                        list_meta_links(ui, list);
+                                        list_meta_links(ui, list, &mut scroll_to);
                                     }
                                 }
                             });
                         });
                         row.col(|ui| {
                             ui.horizontal(|ui| {
-                                for (_, meta) in doc.meta.deref() {
+                                for (_, meta) in meta.deref() {
                                     if let Meta::Obsoletes(list) = meta {
-                                        list_meta_links(ui, list);
+                                        list_meta_links(ui, list, &mut scroll_to);
                                     }
                                 }
                             });
                         });
                         row.col(|ui| {
                             ui.horizontal(|ui| {
-                                for (_, meta) in doc.meta.deref() {
+                                for (_, meta) in meta.deref() {
                                     if let Meta::UpdatedBy(list) = meta {
-                                        list_meta_links(ui, list);
+                                        list_meta_links(ui, list, &mut scroll_to);
                                     }
                                 }
                             });
                         });
                         row.col(|ui| {
                             ui.horizontal(|ui| {
-                                for (_, meta) in doc.meta.deref() {
+                                for (_, meta) in meta.deref() {
                                     if let Meta::ObsoletedBy(list) = meta {
-                                        list_meta_links(ui, list);
+                                        list_meta_links(ui, list, &mut scroll_to);
+                                    }
+                                }
+                            });
+                        });
+                        row.col(|ui| {
+                            ui.horizontal(|ui| {
+                                for (_, meta) in meta.deref() {
+                                    if let Meta::NormativeReferences(list) = meta {
+                                        list_meta_links(ui, list, &mut scroll_to);
+                                    }
+                                }
+                            });
+                        });
+                        row.col(|ui| {
+                            ui.horizontal(|ui| {
+                                for (_, meta) in meta.deref() {
+                                    if let Meta::InformativeReferences(list) = meta {
+                                        list_meta_links(ui, list, &mut scroll_to);
                                     }
                                 }
                             });
                         });
+                        row.col(|ui| {
+                            // citing documents can be thousands, only list them on hover
+                            let mut citing: Vec<&DocIdentifier> = Vec::new();
+                            let (mut normative, mut informative) = (0, 0);
+                            for (_, meta) in meta.deref() {
+                                match meta {
+                                    Meta::NormativelyReferencedBy(list) => {
+                                        normative += list.len();
+                                        citing.extend(list.iter().map(|r| r.deref()));
+                                    }
+                                    Meta::InformativelyReferencedBy(list) => {
+                                        informative += list.len();
+                                        citing.extend(list.iter().map(|r| r.deref()));
+                                    }
+                                    _ => {}
+                                }
+                            }
+                            citing.sort();
+                            let label = ui.label(format!("{normative} N / {informative} I"));
+                            if !citing.is_empty() {
+                                label.on_hover_text(
+                                    citing.iter().map(|id| id.to_string()).collect::<Vec<_>>().join("\n"),
+                                );
+                            }
+                        });
                     });
                 }
             });
+        if let (Some(eq), Some(id)) = (equivalence, read_changed) {
+            let is_read = self.cache.get(&id).is_some_and(|state| state.is_read);
+            eq.set_read(&mut self.cache, &id, is_read);
+        }
+
+        // scrolled to on the next frame
+        if scroll_to.is_some() {
+            ui.ctx().request_repaint();
+        }
+        self.table_scroll_to = scroll_to;
 
         let id: Option<(DocIdentifier, Response)> = ui.memory(|mem| {
            mem.data.get_temp(action_popup)
@@ -180,7 +380,13 @@ impl RFCDepApp {
         if let Some((ref id, ref button)) = id {
             if let Some(result) = self.make_actions_ui(action_popup, id, button, ui) {
                 if let Some(state) = self.cache.get_mut(id) {
-                    *state = result
+                    let upgraded = state.content.summary.revision != result.content.summary.revision;
+                    let refreshed = state.fetch != result.fetch;
+                    *state = result;
+                    // relations of the new revision, or of the refreshed document
+                    if upgraded || refreshed {
+                        self.update_cache(None, true);
+                    }
                 }
             }
         }
@@ -205,22 +411,51 @@ impl RFCDepApp {
                     };
                 });
 
+                if let Some(latest) = state.newer_revision().map(str::to_string) {
+                    ui.horizontal(|ui| {
+                        ui.label("revision");
+                        if ui.small_button(format!("Upgrade to {}", latest)).clicked() {
+                            match IetfDoc::from_name(id.to_string()) {
+                                Ok(doc) => state.select_revision(doc),
+                                Err(err) => {
+                                    self.toasts.error(err).set_duration(Some(Duration::from_secs(5)));
+                                }
+                            }
+                        }
+                    });
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("fetched");
+                    ui.label(fetched_label(state.fetch.fetched_at));
+                    if ui.small_button("Refresh").on_hover_text("query again if its pages changed").clicked() {
+                        match IetfDoc::refresh(&state.content.summary.id, &state.fetch) {
+                            Ok(Refresh::Unchanged(fetch)) => state.fetch = fetch,
+                            Ok(Refresh::Changed(doc, fetch)) => state.refresh_from(*doc, fetch),
+                            Err(err) => {
+                                self.toasts.error(err).set_duration(Some(Duration::from_secs(5)));
+                            }
+                        }
+                    }
+                });
+
                 ui.horizontal(|ui| {
                     ui.label("offline");
-                    if state.offline.is_none() {
-                        if ui.small_button("Save").clicked() {
-                            if let Err(err) = state.download() {
+                    if state.offline.is_empty() {
+                        let preferred = self.settings.download_format;
+                        if ui.small_button("Save").on_hover_text(format!("download as {}", preferred)).clicked() {
+                            if let Err(err) = state.download(preferred) {
                                 self.toasts.error(err).set_duration(Some(Duration::from_secs(5)));
                             }
                         }
                     } else {
-                        if ui.small_button("View").clicked() {
+                        let formats: Vec<String> = state.offline.formats().map(|format| format.to_string()).collect();
+                        if ui.small_button("View").on_hover_text(formats.join(", ")).clicked() {
                             self.open_viewer(id.clone());
-                            println!("{}", state.offline.as_ref().unwrap());
                         }
 
                         if ui.small_button("Forget").clicked() {
-                            state.offline = None;
+                            state.offline.clear();
                         }
                     }
                 });
@@ -26,7 +26,7 @@ impl RFCDepApp {
                 self.make_table_view(ui);
             }
             Tab::Graph => {
-                ui.label("todo!()");
+                self.make_graph_view(ui);
             }
             Tab::Viewer => {
                 self.make_viewer_view(ui)
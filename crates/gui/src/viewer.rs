@@ -1,45 +1,275 @@
+use std::fs;
+use std::time::Duration;
 use eframe::egui;
-use eframe::egui::Ui;
-use rfc_dep_ietf::DocIdentifier;
+use eframe::egui::text::LayoutJob;
+use eframe::egui::{Color32, ComboBox, FontId, Label, TextFormat, TextStyle, Ui};
+use if_chain::if_chain;
+use rfc_dep_ietf::{DiffLine, DiffTag, DocFormat, DocIdentifier, ErratumStatus, Revision};
 use crate::app::RFCDepApp;
+use crate::table_view::erratum_label;
 use crate::tabs::Tab;
 
+/* background of the deleted / inserted lines, stronger for their changed words */
+fn diff_background(tag: DiffTag, changed: bool) -> Color32 {
+    let alpha = if changed { 110 } else { 35 };
+    match tag {
+        DiffTag::Equal => Color32::TRANSPARENT,
+        DiffTag::Delete => Color32::from_rgba_unmultiplied(230, 80, 80, alpha),
+        DiffTag::Insert => Color32::from_rgba_unmultiplied(90, 170, 90, alpha),
+    }
+}
+
+fn diff_line_job(line: &DiffLine, color: Color32) -> LayoutJob {
+    let format = |background| TextFormat {
+        font_id: FontId::monospace(12.0),
+        color,
+        background,
+        ..TextFormat::default()
+    };
+    let prefix = match line.tag {
+        DiffTag::Equal => "  ",
+        DiffTag::Delete => "- ",
+        DiffTag::Insert => "+ ",
+    };
+
+    let mut job = LayoutJob::default();
+    job.append(prefix, 0.0, format(diff_background(line.tag, false)));
+    if line.words.is_empty() {
+        job.append(&line.text, 0.0, format(diff_background(line.tag, false)));
+    } else {
+        for (tag, word) in &line.words {
+            job.append(word, 0.0, format(diff_background(line.tag, *tag != DiffTag::Equal)));
+        }
+    }
+
+    job
+}
+
+fn revision_label(revision: &Revision) -> String {
+    match revision.date {
+        Some(ref date) => format!("{} ({})", revision.revision, date),
+        None => revision.revision.clone(),
+    }
+}
+
 impl RFCDepApp {
 
     pub(crate) fn open_viewer(&mut self, id: DocIdentifier) {
         self.viewed_doc = Some(id);
+        self.viewed_diff = None;
+        self.diff_revisions = Default::default();
+        self.viewed_format = None;
         self.selected_tab = Tab::Viewer
     }
 
+    /* pick two revisions of the viewed draft and diff them */
+    fn make_revisions_ui(&mut self, ui: &mut Ui, id: &DocIdentifier) {
+        let (revisions, selected) = match self.cache.get(id) {
+            Some(state) if !state.content.revisions.is_empty() => {
+                (state.content.revisions.clone(), state.content.summary.revision.clone())
+            }
+            _ => return,
+        };
+
+        // previous revision against the selected one by default
+        let (old, new) = &mut self.diff_revisions;
+        if new.is_empty() {
+            let position = revisions.iter().position(|revision| revision.revision == selected);
+            *old = revisions[position.unwrap_or(0).saturating_sub(1)].revision.clone();
+            *new = selected;
+        }
+
+        let (mut diff_clicked, mut history_clicked) = (false, false);
+        ui.horizontal(|ui| {
+            ui.label("revisions");
+            for (side, revision) in [("diff_old", &mut *old), ("diff_new", &mut *new)] {
+                ComboBox::from_id_source(side).selected_text(revision.clone()).show_ui(ui, |ui| {
+                    for known in &revisions {
+                        ui.selectable_value(revision, known.revision.clone(), revision_label(known));
+                    }
+                });
+            }
+
+            diff_clicked = ui.button("Diff").clicked();
+            if self.viewed_diff.is_some() && ui.button("Text").clicked() {
+                self.viewed_diff = None;
+            }
+            history_clicked = ui.button("History").on_hover_text("query the dates of the revisions").clicked();
+
+            if let Some(ref diff) = self.viewed_diff {
+                ui.label(format!("-{} / +{} lines", diff.deleted(), diff.inserted()));
+            }
+        });
+
+        if history_clicked {
+            if let Err(err) = rfc_dep_project::update_revisions(&mut self.cache, id) {
+                self.toasts.error(err).set_duration(Some(Duration::from_secs(5)));
+            }
+        }
+
+        if diff_clicked {
+            let (old, new) = &self.diff_revisions;
+            match self.cache.get(id).map(|state| state.content.diff_revisions(old, new)) {
+                Some(Ok(diff)) => self.viewed_diff = Some(diff),
+                Some(Err(err)) => {
+                    self.toasts.error(err).set_duration(Some(Duration::from_secs(5)));
+                }
+                None => {}
+            }
+        }
+    }
+
+    /* errata of the viewed rfc, with their original and corrected texts */
+    fn make_errata_ui(&self, ui: &mut Ui, id: &DocIdentifier) {
+        let Some(doc) = self.cache.get(id).map(|state| &state.content) else {
+            return;
+        };
+        let Some(ref errata) = doc.errata else {
+            return;
+        };
+
+        let title = format!(
+            "Errata: {} verified, {} held, {} total",
+            doc.errata_count(ErratumStatus::Verified).unwrap_or_default(),
+            doc.errata_count(ErratumStatus::HeldForDocumentUpdate).unwrap_or_default(),
+            errata.len()
+        );
+        egui::CollapsingHeader::new(title).id_source("viewer_errata").show(ui, |ui| {
+            egui::ScrollArea::vertical().max_height(250.0).show(ui, |ui| {
+                for erratum in errata {
+                    ui.label(erratum_label(erratum));
+                    for (name, text) in [("original", &erratum.original), ("corrected", &erratum.corrected)] {
+                        if let Some(text) = text {
+                            ui.label(name);
+                            ui.add(Label::new(egui::RichText::new(text).monospace()).wrap(false));
+                        }
+                    }
+                    ui.separator();
+                }
+            });
+        });
+    }
+
+    fn make_diff_view(&mut self, ui: &mut Ui) {
+        let Some(ref diff) = self.viewed_diff else {
+            return;
+        };
+
+        let color = ui.visuals().text_color();
+        let row_height = ui.text_style_height(&TextStyle::Monospace);
+        egui::ScrollArea::both().show_rows(ui, row_height, diff.lines.len(), |ui, range| {
+            for line in &diff.lines[range] {
+                ui.add(Label::new(diff_line_job(line, color)).wrap(false));
+            }
+        });
+    }
+
     pub(crate) fn make_viewer_view(&mut self, ui: &mut Ui) {
-        let id = self.viewed_doc.as_ref();
-        if let None = id {
+        let id = self.viewed_doc.clone();
+        if id.is_none() {
             ui.label("Open a document to view it here.");
             return;
         }
 
         let id = id.unwrap();
 
-        let doc = self.cache.get_mut(id);
-        if let None = doc {
+        if !self.cache.has_id(&id) {
             ui.label(format!("Document \"{id}\" is not in cache, please include it."));
             return;
         }
 
-        if let None = doc.as_ref().unwrap().offline {
+        self.make_revisions_ui(ui, &id);
+        self.make_errata_ui(ui, &id);
+        if self.viewed_diff.is_some() {
+            ui.separator();
+            self.make_diff_view(ui);
+            return;
+        }
+
+        self.make_offline_view(ui, &id);
+    }
+
+    /* downloaded copy of the viewed document, in the format picked among the downloaded ones */
+    fn make_offline_view(&mut self, ui: &mut Ui, id: &DocIdentifier) {
+        let preferred = self.settings.download_format;
+        let Some(doc) = self.cache.get_mut(id) else {
+            return;
+        };
+
+        if doc.offline.is_empty() {
+            let mut download = false;
             ui.vertical(|ui| {
                 ui.label(format!("Document \"{id}\" is not downloaded, please save it before viewing."));
-                if ui.button("download").clicked() {
-                    let _ = doc.unwrap().download();
-                }
+                download = ui.button(format!("download ({})", preferred)).clicked();
             });
+            if download {
+                if let Err(err) = doc.download(preferred) {
+                    self.toasts.error(err).set_duration(Some(Duration::from_secs(5)));
+                }
+            }
             return;
         }
 
-        egui::ScrollArea::vertical().show(ui, |ui| {
-            ui.vertical_centered_justified(|ui| {
-                ui.text_edit_multiline(&mut doc.unwrap().offline.as_mut().unwrap().as_str());
-            });
+        let Some(format) = self
+            .viewed_format
+            .filter(|format| doc.offline.has(*format))
+            .or_else(|| doc.offline.best_format(preferred))
+        else {
+            return;
+        };
+
+        let (mut picked, mut download, mut export) = (None, None, false);
+        ui.horizontal(|ui| {
+            ui.label("format");
+            for other in DocFormat::ALL {
+                if doc.offline.has(other) {
+                    if ui.selectable_label(other == format, other.to_string()).clicked() {
+                        picked = Some(other);
+                    }
+                } else if ui.small_button(format!("+ {}", other)).on_hover_text("download this format").clicked() {
+                    download = Some(other);
+                }
+            }
+            export = ui.button("Export").on_hover_text("save this copy to a file").clicked();
         });
+
+        if let Some(other) = download {
+            match doc.download_format(other) {
+                Ok(()) => picked = Some(other),
+                Err(err) => {
+                    self.toasts.error(err).set_duration(Some(Duration::from_secs(5)));
+                }
+            }
+        }
+
+        if_chain! {
+            if export;
+            if let Some(bytes) = doc.offline.bytes(format);
+            if let Some(path) = rfd::FileDialog::new()
+                .set_file_name(&format!("{}.{}", doc.content.summary.id, format))
+                .add_filter(format.extension(), &[format.extension()])
+                .save_file();
+            if let Err(err) = fs::write(path, bytes);
+            then {
+                self.toasts.error(err.to_string()).set_duration(Some(Duration::from_secs(5)));
+            }
+        }
+
+        match doc.offline.text(format) {
+            Some(mut text) => {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    ui.vertical_centered_justified(|ui| {
+                        ui.text_edit_multiline(&mut text);
+                    });
+                });
+            }
+            None => {
+                ui.label(format!("The {} copy can't be shown here, export it to read it.", format));
+            }
+        }
+
+        if picked.is_some() {
+            self.viewed_format = picked;
+        }
     }
-}
\ No newline at end of file
+}
@@ -1,22 +1,29 @@
-use crate::error::{DocError::*, Result};
+use crate::error::{DocError, DocError::*, Result};
+use crate::index::format_date;
 use crate::meta::Meta;
+use crate::source::{block_on, default_source, DocSource};
 use crate::url::SourceUrl;
-use crate::{IdContainer, MetaKey};
-use fast_xml::events::Event;
-use fast_xml::Reader;
+use crate::xml;
+use crate::{DocIdentifier, Erratum, IdContainer, LookupQuery, MetaKey, Revision};
 use rayon::iter::Either;
-use rayon::prelude::*;
 use regex::bytes::Regex;
-use reqwest::blocking::Response;
-use reqwest::StatusCode;
-use scraper::{Html, Selector};
+use scraper::{ElementRef, Html, Selector};
 use serde::{Deserialize, Serialize};
-use std::fmt::{Debug, Display};
+use std::fmt::Debug;
+use std::future::Future;
 use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use url::Url;
 use variant_map::hashmap::Map;
 
-/* Identify IETF documents by String (internal name) for now */
-pub type DocIdentifier = String;
+/* Whether documents are queried along with the documents citing them (datatracker referenced-by page)
+ * one more query per document, on by default */
+static QUERY_REFERENCED_BY: AtomicBool = AtomicBool::new(true);
+
+pub fn set_query_referenced_by(query: bool) {
+    QUERY_REFERENCED_BY.store(query, Ordering::Relaxed);
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 // C represents the container type used to hold document references
@@ -26,16 +33,119 @@ pub struct IetfDoc<C>
 {
     pub summary: Summary,
     pub meta: MetaMap<C>,
+    // revisions of a draft, oldest first, dated by IetfDoc::revision_history
+    #[serde(default)]
+    pub revisions: Vec<Revision>,
+    // errata reports of an rfc, None until queried (see IetfDoc::update_errata)
+    #[serde(default)]
+    pub errata: Option<Vec<Erratum>>,
 }
 
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Summary {
-    pub id: String,
+    pub id: DocIdentifier,
     pub revision: String,
     pub is_rfc: bool,
     pub url: SourceUrl,
     pub title: String,
+    // standard level (Proposed Standard, Informational...) and publication date (YYYY[-MM[-DD]])
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub date: Option<String>,
+    #[serde(default)]
+    pub authors: Vec<String>,
+    // expiry date of drafts
+    #[serde(default)]
+    pub expires: Option<String>,
+    // IETF, IRTF, IAB, ISE...
+    #[serde(default)]
+    pub stream: Option<String>,
+    // working / research group acronym
+    #[serde(default)]
+    pub group: Option<String>,
+    #[serde(default)]
+    pub pages: Option<u32>,
+    #[serde(default)]
+    pub abstract_text: Option<String>,
+}
+
+/* Summary fields read from a source (datatracker json / html, xml, text), None when unknown */
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SummaryDetails {
+    pub(crate) status: Option<String>,
+    pub(crate) date: Option<String>,
+    pub(crate) authors: Vec<String>,
+    pub(crate) expires: Option<String>,
+    pub(crate) stream: Option<String>,
+    pub(crate) group: Option<String>,
+    pub(crate) pages: Option<u32>,
+    pub(crate) abstract_text: Option<String>,
+}
+
+impl Summary {
+    /* summary without any detail, see Summary::complete */
+    pub fn new(id: DocIdentifier, revision: String, is_rfc: bool, title: String) -> Result<Summary> {
+        Ok(Summary {
+            url: SourceUrl::new(&id)?,
+            id,
+            revision,
+            is_rfc,
+            title,
+            status: None,
+            date: None,
+            authors: Vec::new(),
+            expires: None,
+            stream: None,
+            group: None,
+            pages: None,
+            abstract_text: None,
+        })
+    }
+
+    /* fill the missing fields with the ones of another summary of the document (eg: an older query of it)
+     * returns the number of fields filled */
+    pub fn complete_from(&mut self, other: &Summary) -> usize {
+        self.complete(SummaryDetails {
+            status: other.status.clone(),
+            date: other.date.clone(),
+            authors: other.authors.clone(),
+            expires: other.expires.clone(),
+            stream: other.stream.clone(),
+            group: other.group.clone(),
+            pages: other.pages,
+            abstract_text: other.abstract_text.clone(),
+        })
+    }
+
+    /* fill the missing fields with the known details, returns the number of fields filled */
+    pub(crate) fn complete(&mut self, details: SummaryDetails) -> usize {
+        let mut added = 0;
+        let mut fill = |field: &mut Option<String>, value: Option<String>| {
+            if field.is_none() && value.is_some() {
+                *field = value;
+                added += 1;
+            }
+        };
+        fill(&mut self.status, details.status);
+        fill(&mut self.date, details.date);
+        fill(&mut self.expires, details.expires);
+        fill(&mut self.stream, details.stream);
+        fill(&mut self.group, details.group);
+        fill(&mut self.abstract_text, details.abstract_text);
+
+        if self.pages.is_none() && details.pages.is_some() {
+            self.pages = details.pages;
+            added += 1;
+        }
+        if self.authors.is_empty() && !details.authors.is_empty() {
+            self.authors = details.authors;
+            added += 1;
+        }
+
+        added
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,9 +155,7 @@ pub struct MetaMap<C>(Map<MetaKey, Meta<C>>)
 impl<C> Default for MetaMap<C>
     where C: IdContainer {
     fn default() -> Self {
-        Self {
-            0: Default::default(),
-        }
+        Self(Default::default())
     }
 }
 
@@ -55,7 +163,7 @@ impl<C> MetaMap<C>
     where
         C: IdContainer,
 {
-    fn push_meta(&mut self, meta: Meta<C>) -> Option<Meta<C>> {
+    pub(crate) fn push_meta(&mut self, meta: Meta<C>) -> Option<Meta<C>> {
         self.insert(meta)
     }
 
@@ -80,18 +188,24 @@ impl<C> DerefMut for MetaMap<C>
     }
 }
 
-pub fn name_to_id(name: impl Into<String>) -> DocIdentifier {
-    name.into().replace(' ', "").to_lowercase()
+/* parse a document name, see DocIdentifier::from_str for the accepted forms */
+pub fn name_to_id(name: impl AsRef<str>) -> Result<DocIdentifier> {
+    name.as_ref().parse()
 }
 
-fn http_get<T: reqwest::IntoUrl + Display>(url: T) -> Result<Response> {
-    let resp = reqwest::blocking::get(url)?;
-    let status_code = resp.status();
-    if !StatusCode::is_success(&status_code) {
-        return Query(format!("Error querying {}: {}", resp.url(), status_code)).into();
+/* working / research group of a wg draft name: draft-ietf-tcpm-rfc793bis => tcpm */
+pub(crate) fn group_from_name(id: &DocIdentifier) -> Option<String> {
+    let name = match id {
+        DocIdentifier::Draft { name, .. } => name,
+        _ => return None,
+    };
+    let mut parts = name.split('-');
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some("draft"), Some("ietf" | "irtf"), Some(group), Some(_)) if !group.is_empty() => {
+            Some(group.to_string())
+        }
+        _ => None,
     }
-
-    Ok(resp)
 }
 
 // TODO better api
@@ -104,116 +218,294 @@ impl<C> IetfDoc<C>
     }
 
     pub fn from_name(name: impl Into<String>) -> Result<IetfDoc<C>> {
-        let source = Self::id_to_url(&name.into())?;
-        IetfDoc::from_html(Either::Left(&source))
+        let name = name.into();
+        block_on(Self::from_name_async(name))
+    }
+
+    pub fn from_name_with(source: &dyn DocSource, name: impl Into<String>) -> Result<IetfDoc<C>> {
+        let name = name.into();
+        block_on(Self::from_name_with_async(source, name))
+    }
+
+    pub async fn from_name_async(name: impl Into<String>) -> Result<IetfDoc<C>> {
+        let source = default_source();
+        Self::from_name_with_async(source.as_ref(), name).await
+    }
+
+    pub async fn from_name_with_async(source: &dyn DocSource, name: impl Into<String>) -> Result<IetfDoc<C>> {
+        let id = name_to_id(name.into())?;
+        // subseries have no document page, see from_subseries
+        if id.is_subseries() {
+            return IetfDoc::from_subseries(source, &id, None).await;
+        }
+
+        let url = Self::id_to_url(&id)?;
+        IetfDoc::from_html(source, Either::Left(&url)).await
     }
 
     pub fn from_summary(summary: Summary) -> Result<IetfDoc<C>> {
-        IetfDoc::from_html(Either::Right(summary))
+        block_on(Self::from_summary_async(summary))
+    }
+
+    pub fn from_summary_with(source: &dyn DocSource, summary: Summary) -> Result<IetfDoc<C>> {
+        block_on(Self::from_summary_with_async(source, summary))
+    }
+
+    pub async fn from_summary_async(summary: Summary) -> Result<IetfDoc<C>> {
+        let source = default_source();
+        Self::from_summary_with_async(source.as_ref(), summary).await
+    }
+
+    pub async fn from_summary_with_async(source: &dyn DocSource, summary: Summary) -> Result<IetfDoc<C>> {
+        if summary.id.is_subseries() {
+            let id = summary.id.clone();
+            return IetfDoc::from_subseries(source, &id, Some(summary)).await;
+        }
+
+        IetfDoc::from_html(source, Either::Right(summary)).await
+    }
+
+    fn selector(url: &Url, selector: &str) -> Result<Selector> {
+        Selector::parse(selector).map_err(|_| DocError::layout(url, selector))
+    }
+
+    fn select_first<'a>(url: &Url, element: ElementRef<'a>, selector: &str) -> Result<ElementRef<'a>> {
+        element
+            .select(&Self::selector(url, selector)?)
+            .next()
+            .ok_or_else(|| DocError::layout(url, selector))
     }
 
-    fn xml_is_available(document: &Html) -> bool {
-        let selector = Selector::parse("div.buttonlist a").unwrap();
+    fn xml_is_available(url: &Url, document: &Html) -> Result<bool> {
+        let selector = Self::selector(url, "div.buttonlist a")?;
         for button in document.select(&selector) {
             if button.text().any(|text| text.eq("xml")) {
-                return true;
+                return Ok(true);
             }
         }
 
-        false
+        Ok(false)
     }
 
-    fn from_html(source: Either<&SourceUrl, Summary>) -> Result<IetfDoc<C>> {
-        let (url, summary_provided) = match source {
-            Either::Left(url) => (url.html(), false),
-            Either::Right(ref summary) => (summary.url.html(), true),
+    async fn from_html(doc_source: &dyn DocSource, source: Either<&SourceUrl, Summary>) -> Result<IetfDoc<C>> {
+        let url = match source {
+            Either::Left(url) => url.html()?,
+            Either::Right(ref summary) => summary.url.html()?,
         };
 
-        let resp = http_get(url.as_str())?;
+        let resp = doc_source.get(&url).await?;
         if resp.url().path() == "/doc/search" {
-            return Query(format!("Error querying {}: document doesn't exist", url)).into();
+            return NotFound {
+                url: url.to_string(),
+                source: None,
+            }
+            .into();
         }
 
+        let page_url = resp.url().clone();
         let text = resp.text()?;
-        let document = Html::parse_document(&text);
+        let (mut summary, revisions, html_meta) = Self::parse_page_html(&page_url, &text, source.right())?;
+
+        let mut doc_meta = match html_meta {
+            Some(doc_meta) => doc_meta,
+            None => {
+                let (xml_meta, details) = Self::parse_meta_xml(doc_source, &summary.url, summary.is_rfc).await?;
+                summary.complete(details);
+                xml_meta
+            }
+        };
+
+        // references from the xml are more complete than the datatracker references page
+        let has_references = doc_meta.values().any(|meta| {
+            matches!(meta, Meta::NormativeReferences(_) | Meta::InformativeReferences(_))
+        });
+
+        for referenced_by in [false, true] {
+            let skip = match referenced_by {
+                true => !QUERY_REFERENCED_BY.load(Ordering::Relaxed),
+                false => has_references,
+            };
+            if skip {
+                continue;
+            }
+
+            for meta in Self::parse_references_html(doc_source, &summary.url, referenced_by).await? {
+                doc_meta.push_meta(meta);
+            }
+        }
+
+        let doc = IetfDoc {
+            summary,
+            meta: doc_meta,
+            revisions,
+            errata: None,
+        };
+
+        Ok(doc)
+    }
+
+    /* summary (unless provided), revisions of drafts and metas of a datatracker document page
+     * metas are None when they have to be read from the xml of the document
+     * the parsed page isn't Send so it must not be held across queries */
+    fn parse_page_html(
+        url: &Url,
+        text: &str,
+        provided: Option<Summary>,
+    ) -> Result<(Summary, Vec<Revision>, Option<MetaMap<C>>)> {
+        let document = Html::parse_document(text);
 
         // Find Document Title and Name
-        let summary = if !summary_provided {
-            let selector = scraper::Selector::parse("#content > h1").unwrap();
-            let title_elem = document.select(&selector).next().unwrap();
-            let title_text = title_elem.text().collect::<String>();
-            let title_regex = Regex::new(r"^\s+(.+)\s+(.+)\s$").unwrap();
-            let title_captures = title_regex.captures(title_text.as_ref()).unwrap();
-            let title =
-                String::from_utf8(title_captures.get(1).unwrap().as_bytes().to_vec()).unwrap();
-            let name =
-                String::from_utf8(title_captures.get(2).unwrap().as_bytes().to_vec()).unwrap();
-            let id = name_to_id(name);
-
-            let is_rfc = id.starts_with("rfc");
-            let revision = if is_rfc {
-                let selector =
-                    scraper::Selector::parse(".revision-list li.page-item:not(.rfc)").unwrap();
-                document.select(&selector).last()
-            } else {
-                let selector =
-                    scraper::Selector::parse(".revision-list li.page-item.active").unwrap();
-                document.select(&selector).next()
+        let mut summary = match provided {
+            Some(summary) => summary,
+            None => {
+                let title_elem = Self::select_first(url, document.root_element(), "#content > h1")?;
+                let title_text = title_elem.text().collect::<String>();
+                let title_regex = Regex::new(r"^\s+(.+)\s+(.+)\s$").map_err(|err| Layout {
+                    url: url.to_string(),
+                    selector: "#content > h1".to_string(),
+                    source: Some(Arc::new(err)),
+                })?;
+                let title_captures = title_regex
+                    .captures(title_text.as_ref())
+                    .ok_or_else(|| DocError::layout(url, "#content > h1"))?;
+                let title = String::from_utf8_lossy(&title_captures[1]).to_string();
+                let name = String::from_utf8_lossy(&title_captures[2]).to_string();
+                let mut id = name_to_id(name)?;
+
+                let is_rfc = id.is_rfc();
+                // the revision in the name of a draft is the one shown by the page
+                let revision = match id.revision() {
+                    Some(revision) => Some(revision),
+                    None if is_rfc => {
+                        let selector = Self::selector(url, ".revision-list li.page-item:not(.rfc)")?;
+                        document.select(&selector).next_back().map(|x| x.text().map(str::trim).collect::<String>())
+                    }
+                    None => {
+                        let selector = Self::selector(url, ".revision-list li.page-item.active")?;
+                        document.select(&selector).next().map(|x| x.text().map(str::trim).collect::<String>())
+                    }
+                }
+                .unwrap_or("00".to_string());
+
+                // ids of drafts include their revision
+                if let DocIdentifier::Draft { revision: ref mut id_revision @ None, .. } = id {
+                    *id_revision = revision.parse().ok();
+                }
+                Summary::new(id, revision, is_rfc, title)?
             }
+        };
+        summary.complete(Self::parse_summary_html(url, &document, summary.is_rfc)?);
+        if summary.group.is_none() {
+            summary.group = group_from_name(&summary.id);
+        }
+
+        // revisions of an rfc are the ones of the draft it was
+        let mut revisions = Vec::new();
+        if !summary.is_rfc {
+            let selector = Self::selector(url, ".revision-list li.page-item:not(.rfc)")?;
+            revisions = document
+                .select(&selector)
                 .map(|x| x.text().map(str::trim).collect::<String>())
-                .unwrap_or("00".to_string());
+                .filter(|revision| !revision.is_empty() && revision.chars().all(|c| c.is_ascii_digit()))
+                .map(|revision| Revision { revision, date: None })
+                .collect();
+            if let (true, Some(revision)) = (revisions.is_empty(), summary.id.revision()) {
+                revisions.push(Revision { revision, date: None });
+            }
+        }
 
-            Some(Summary {
-                url: Self::id_to_url(&id)?,
-                id, // includes revision (for drafts)
-                revision,
-                is_rfc,
-                title,
-            })
+        let doc_meta = if summary.is_rfc || !Self::xml_is_available(url, &document)? {
+            Some(Self::parse_meta_html(url, &document)?)
         } else {
             None
         };
-        let summary = summary.unwrap_or_else(|| source.right().unwrap());
 
-        let doc_meta = if summary.is_rfc || !Self::xml_is_available(&document) {
-            Self::parse_meta_html(&document)?
-        } else {
-            Self::parse_meta_xml(&summary.url, summary.is_rfc)?
-        };
+        Ok((summary, revisions, doc_meta))
+    }
 
-        let doc = IetfDoc {
-            summary,
-            meta: doc_meta,
-        };
+    /* summary details of the rows of the meta table of a datatracker document page
+     *   Type: "RFC - Proposed Standard (August 2022)", "Active Internet-Draft (tcpm WG)"
+     *   Authors, Last updated (date of the draft revision), RFC stream / Stream, Intended RFC status */
+    fn parse_summary_html(url: &Url, document: &Html, is_rfc: bool) -> Result<SummaryDetails> {
+        let row_selector =
+            Self::selector(url, "#content > table > tbody.meta.align-top.border-top > tr")?;
+        let author_selector = Self::selector(url, "a[href*=\"/person/\"]")?;
+        static RFC: OnceLock<regex::Regex> = OnceLock::new();
+        static GROUP: OnceLock<regex::Regex> = OnceLock::new();
+        static DATE: OnceLock<regex::Regex> = OnceLock::new();
+        let rfc_regex =
+            RFC.get_or_init(|| regex::Regex::new(r"RFC\s*-\s*([A-Za-z ]+?)\s*\((\w+) (\d{4})\)").unwrap());
+        let group_regex = GROUP.get_or_init(|| regex::Regex::new(r"\(([\w-]+) [WR]G\)").unwrap());
+        let date_regex = DATE.get_or_init(|| regex::Regex::new(r"(\d{4})-(\d{2})-(\d{2})").unwrap());
+
+        let mut details = SummaryDetails::default();
+        for row in document.select(&row_selector) {
+            let name = match row.select(&Self::selector(url, "th:last-of-type")?).next() {
+                Some(name) => name.text().collect::<String>(),
+                None => continue,
+            };
+            let value = match row.select(&Self::selector(url, "td:not(.edit):last-of-type")?).next() {
+                Some(value) => value,
+                None => continue,
+            };
+            let text = value.text().collect::<Vec<_>>().join(" ");
+            let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+            match name.trim() {
+                "Type" => {
+                    if let Some(captures) = rfc_regex.captures(&text) {
+                        details.status = Some(captures[1].to_string());
+                        details.date = Some(format_date(&captures[3], Some(&captures[2]), None));
+                    }
+                    details.group = group_regex.captures(&text).map(|captures| captures[1].to_string());
+                }
+                "Authors" => {
+                    details.authors = value
+                        .select(&author_selector)
+                        .map(|author| author.text().collect::<String>().trim().to_string())
+                        .filter(|author| !author.is_empty())
+                        .collect();
+                }
+                // rfcs are updated after their publication
+                "Last updated" if !is_rfc => {
+                    details.date = date_regex.find(&text).map(|date| date.as_str().to_string());
+                }
+                "RFC stream" | "Stream" => {
+                    details.stream = text.split_whitespace().next().map(str::to_string);
+                }
+                "Intended RFC status" => details.status = Some(text).filter(|text| !text.is_empty()),
+                _ => {}
+            }
+        }
 
-        Ok(doc)
+        Ok(details)
     }
 
-    fn parse_meta_html(document: &Html) -> Result<MetaMap<C>> {
+    fn parse_meta_html(url: &Url, document: &Html) -> Result<MetaMap<C>> {
         let row_selector =
-            Selector::parse("#content > table > tbody.meta.align-top.border-top > tr").unwrap();
-        let row_name_selector = Selector::parse("th:last-of-type").unwrap();
-        let row_value_selector = Selector::parse("td:not(.edit):last-of-type").unwrap();
+            Self::selector(url, "#content > table > tbody.meta.align-top.border-top > tr")?;
+        let div_selector = Self::selector(url, "div")?;
         let meta_elems = document.select(&row_selector).collect::<Vec<_>>();
         let mut doc_meta: MetaMap<C> = MetaMap::default();
         for row in meta_elems {
-            let name = row
-                .select(&row_name_selector)
-                .next()
-                .unwrap()
+            let name = Self::select_first(url, row, "th:last-of-type")?
                 .text()
                 .collect::<String>();
             let name = name.trim();
-            let value = row.select(&row_value_selector).next().unwrap();
+            let value = Self::select_first(url, row, "td:not(.edit):last-of-type")?;
 
             let metas: Vec<Result<Meta<C>>> = match name {
                 "Type" => value
-                    .select(&Selector::parse("div").unwrap())
+                    .select(&div_selector)
                     .filter_map(|div| {
                         let text: Vec<_> = div.text().collect();
                         if !text.is_empty() {
-                            let tyype = text[0].trim().to_lowercase().replace(' ', "_");
-                            Some(Meta::from_html(tyype, text))
+                            let kind = text[0].trim().to_lowercase().replace(' ', "_");
+                            // status of the document itself, see parse_summary_html
+                            if kind.starts_with("rfc_-") || kind.contains("internet-draft") {
+                                return None;
+                            }
+                            Some(Meta::from_html(kind, text))
                         } else {
                             None
                         }
@@ -235,16 +527,21 @@ impl<C> IetfDoc<C>
                         .collect();
                     vec![Meta::from_html("replaced_by".to_string(), replaced_by)]
                 }
+                // summary details, see parse_summary_html
+                "Authors" | "Last updated" | "RFC stream" | "Stream" | "Intended RFC status" => Vec::new(),
                 _ => {
                     vec![Err(UnknownMeta(name.to_string()))]
                 }
             };
 
             for meta in metas {
-                if let Ok(meta) = meta {
-                    doc_meta.push_meta(meta);
-                } else {
-                    println!("Error: {}", meta.err().unwrap())
+                match meta {
+                    Ok(meta) => {
+                        doc_meta.push_meta(meta);
+                    }
+                    // rows which aren't relations
+                    Err(UnknownMeta(_)) => {}
+                    Err(err) => return Err(err),
                 }
             }
         }
@@ -252,115 +549,132 @@ impl<C> IetfDoc<C>
         Ok(doc_meta)
     }
 
-    // used only on drafts to get the metas
-    fn parse_meta_xml(url: &SourceUrl, is_rfc: bool) -> Result<MetaMap<C>> {
-        let resp = http_get(url.xml(is_rfc)?)?;
-        let bytes = resp.bytes()?;
-        let mut xml = Reader::from_bytes(bytes.as_ref());
-        let mut buf = Vec::new();
-        let mut metas: MetaMap<C> = MetaMap::default();
-
-        loop {
-            match xml.read_event(&mut buf) {
-                Ok(Event::Start(ref e)) if e.name() == b"rfc" => {
-                    for attribute in e.attributes() {
-                        match attribute {
-                            Ok(ref a) => {
-                                let meta = Meta::from_xml(a);
-                                if let Ok(meta) = meta {
-                                    metas.push_meta(meta);
-                                } else {
-                                    println!("{}", meta.err().unwrap())
-                                }
-                            }
-                            Err(e) => {
-                                println!("{}", e);
-                            }
-                        }
-                    }
-                    break;
-                }
-                Ok(Event::Eof) => {
-                    break;
-                }
-                Ok(_) => {}
-                Err(e) => {
-                    println!("{}", e);
-                }
-            }
-            buf.clear();
-        }
+    // query the datatracker references (or referenced by) page of a document
+    async fn parse_references_html(
+        source: &dyn DocSource,
+        url: &SourceUrl,
+        referenced_by: bool,
+    ) -> Result<Vec<Meta<C>>> {
+        let page = if referenced_by {
+            url.referenced_by()?
+        } else {
+            url.references()?
+        };
+        let text = source.get(&page).await?.text()?;
 
-        Ok(metas)
+        Self::parse_references_page(&page, &text, referenced_by)
     }
 
-    pub fn lookup(title: &str, limit: usize, include_drafts: bool) -> Result<Vec<Summary>> {
-        if title.is_empty() {
-            return Lookup("no query".to_string()).into();
-        }
+    fn parse_references_page(url: &Url, text: &str, referenced_by: bool) -> Result<Vec<Meta<C>>> {
+        let document = Html::parse_document(text);
+
+        let header_selector = Self::selector(url, "table thead th")?;
+        let row_selector = Self::selector(url, "table tbody tr")?;
+        let cell_selector = Self::selector(url, "td")?;
+        let link_selector = Self::selector(url, "a")?;
+
+        let type_column = document
+            .select(&header_selector)
+            .position(|th| th.text().collect::<String>().trim().eq_ignore_ascii_case("type"))
+            .unwrap_or(3);
+
+        let rows = document
+            .select(&row_selector)
+            .filter_map(|row| {
+                let cells: Vec<_> = row.select(&cell_selector).collect();
+                let name_cell = cells.first()?;
+                let kind = cells.get(type_column)?.text().collect::<String>();
+
+                // prefer the document link (/doc/{name}/) over the displayed name
+                let name = name_cell
+                    .select(&link_selector)
+                    .next()
+                    .and_then(|a| a.value().attr("href"))
+                    .and_then(|href| href.rsplit_terminator('/').next())
+                    .map(str::to_string)
+                    .unwrap_or_else(|| name_cell.text().collect::<String>());
+
+                Some((name_to_id(name.trim()).ok()?, kind.trim().to_lowercase()))
+            })
+            .collect();
 
-        let rfc_only = if include_drafts { "" } else { "&states__in=3" };
-        let query = format!("https://datatracker.ietf.org/api/v1/doc/document/?title__icontains={title}&limit={limit}&offset=0&format=json{rfc_only}&type__in=draft");
+        Ok(Meta::from_references_html(rows, referenced_by))
+    }
 
-        println!("query = {query}");
-        let resp = reqwest::blocking::get(query);
-        let resp = if let Ok(resp) = resp {
-            resp
-        } else {
-            return Lookup(format!("could not http/GET {}", resp.err().unwrap())).into();
-        };
+    // used only on drafts to get the metas and the summary details missing from the page
+    async fn parse_meta_xml(
+        source: &dyn DocSource,
+        url: &SourceUrl,
+        is_rfc: bool,
+    ) -> Result<(MetaMap<C>, SummaryDetails)> {
+        let resp = source.get(&url.xml(is_rfc)?).await?;
+
+        Ok((
+            xml::parse_meta_xml(resp.url(), resp.bytes())?,
+            xml::parse_summary_xml(resp.url(), resp.bytes())?,
+        ))
+    }
 
-        let status_code = &resp.status();
-        if !StatusCode::is_success(status_code) {
-            return Lookup(format!(
-                "unsuccessful status http/GET status {}",
-                status_code
-            ))
-                .into();
-        }
+    pub fn lookup(title: &str, limit: usize, include_drafts: bool) -> Result<Vec<Summary>> {
+        block_on(Self::lookup_async(title, limit, include_drafts))
+    }
 
-        let summaries: Vec<Summary> = resp
-            .json::<serde_json::Value>()
-            .unwrap()
-            .get_mut("objects")
-            .unwrap()
-            .as_array_mut()
-            .unwrap()
-            .par_drain(..)
-            .map(|obj| {
-                let rfc_num = obj.get("rfc");
-                let revision = obj.get("rev").unwrap().as_str().unwrap().to_string();
-                let id = if rfc_num.is_some_and(|val| !val.is_null()) {
-                    format!("rfc{}", rfc_num.unwrap().as_str().unwrap())
-                } else {
-                    format!(
-                        "{}-{}",
-                        obj.get("name").unwrap().as_str().unwrap(),
-                        revision
-                    )
-                };
-
-                Summary {
-                    url: Self::id_to_url(&id).unwrap(),
-                    id,
-                    revision,
-                    title: obj.get("title").unwrap().as_str().unwrap().to_string(),
-                    is_rfc: rfc_num.is_some(),
-                }
-            })
-            .collect();
+    pub fn lookup_with(
+        source: &dyn DocSource,
+        title: &str,
+        limit: usize,
+        include_drafts: bool,
+    ) -> Result<Vec<Summary>> {
+        block_on(Self::lookup_with_async(source, title, limit, include_drafts))
+    }
 
-        println!("{} matches = {:#?}", summaries.len(), &summaries);
+    pub async fn lookup_async(title: &str, limit: usize, include_drafts: bool) -> Result<Vec<Summary>> {
+        let source = default_source();
+        Self::lookup_with_async(source.as_ref(), title, limit, include_drafts).await
+    }
 
-        Ok(summaries)
+    pub async fn lookup_with_async(
+        source: &dyn DocSource,
+        title: &str,
+        limit: usize,
+        include_drafts: bool,
+    ) -> Result<Vec<Summary>> {
+        let query = LookupQuery::new()
+            .title(title)
+            .limit(limit)
+            .include_drafts(include_drafts);
+
+        Self::lookup_query_with_async(source, &query).await
     }
 
     pub fn download_raw(&self) -> Result<String> {
+        block_on(self.download_raw_async())
+    }
+
+    pub fn download_raw_with(&self, source: &dyn DocSource) -> Result<String> {
+        block_on(self.download_raw_with_async(source))
+    }
+
+    // the returned futures don't borrow the document, which may not be Sync
+    pub fn download_raw_async(&self) -> impl Future<Output = Result<String>> + Send {
+        let url = self.summary.url.raw(self.summary.is_rfc);
+        async move {
+            let source = default_source();
+            Self::download_url(source.as_ref(), url?).await
+        }
+    }
+
+    pub fn download_raw_with_async<'a>(
+        &self,
+        source: &'a dyn DocSource,
+    ) -> impl Future<Output = Result<String>> + Send + 'a {
+        let url = self.summary.url.raw(self.summary.is_rfc);
+        async move { Self::download_url(source, url?).await }
+    }
 
-        let is_rfc = self.summary.is_rfc;
-        let url = self.summary.url.raw(is_rfc)?;
-        let resp = http_get(url)?;
+    pub(crate) async fn download_url(source: &dyn DocSource, url: Url) -> Result<String> {
+        let resp = source.get(&url).await?;
 
-        Ok(resp.text()?)
+        resp.text()
     }
 }
@@ -0,0 +1,164 @@
+use crate::error::{DocError, DocError::*, Result};
+use crate::source::{block_on, default_source, DocSource};
+use crate::url::url_config;
+use crate::{DocIdentifier, IdContainer, IetfDoc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ErratumStatus {
+    Reported,
+    Verified,
+    HeldForDocumentUpdate,
+    Rejected,
+}
+
+impl ErratumStatus {
+    /* status of the errata dump: "Verified", "Held for Document Update"... */
+    fn from_name(name: &str) -> Option<ErratumStatus> {
+        match name.trim().to_lowercase().as_str() {
+            "reported" => Some(ErratumStatus::Reported),
+            "verified" => Some(ErratumStatus::Verified),
+            "held for document update" | "held" => Some(ErratumStatus::HeldForDocumentUpdate),
+            "rejected" => Some(ErratumStatus::Rejected),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ErratumType {
+    Technical,
+    Editorial,
+}
+
+/* An errata report of an rfc, texts are as submitted (with their line breaks) */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Erratum {
+    pub id: u32,
+    // technical or editorial, "tyype" in the projects saved before it was renamed
+    #[serde(alias = "tyype")]
+    pub kind: ErratumType,
+    pub status: ErratumStatus,
+    pub section: Option<String>,
+    pub original: Option<String>,
+    pub corrected: Option<String>,
+}
+
+/* errata reports of the rfcs, by rfc */
+pub type ErrataMap = HashMap<DocIdentifier, Vec<Erratum>>;
+
+/* erratum of an entry of the errata dump, None for entries which aren't rfc errata */
+fn erratum_from_json(origin: &impl Display, obj: &Value) -> Result<Option<(DocIdentifier, Erratum)>> {
+    let field = |key: &str| -> Option<String> {
+        match obj.get(key) {
+            Some(Value::String(value)) => Some(value.trim().to_string()).filter(|value| !value.is_empty()),
+            Some(Value::Number(value)) => Some(value.to_string()),
+            _ => None,
+        }
+    };
+    let required = |key: &str| field(key).ok_or_else(|| DocError::json(origin, format!("missing '{}' in {}", key, obj)));
+
+    // "RFC9293"
+    let Ok(id) = required("doc-id")?.parse::<DocIdentifier>() else {
+        return Ok(None);
+    };
+    let erratum_id = required("errata_id")?;
+    let status = required("errata_status_code")?;
+
+    let erratum = Erratum {
+        id: erratum_id
+            .parse()
+            .map_err(|_| DocError::json(origin, format!("wrong erratum id '{}'", erratum_id)))?,
+        kind: match field("errata_type_code").as_deref() {
+            Some("Technical") => ErratumType::Technical,
+            _ => ErratumType::Editorial,
+        },
+        status: ErratumStatus::from_name(&status)
+            .ok_or_else(|| DocError::json(origin, format!("unknown erratum status '{}'", status)))?,
+        section: field("section"),
+        original: field("orig_text"),
+        corrected: field("correct_text"),
+    };
+
+    Ok(id.is_rfc().then_some((id, erratum)))
+}
+
+/* errata reports of an rfc-editor errata dump (https://www.rfc-editor.org/errata.json)
+ * origin is the url or path of the dump, used in errors */
+pub fn parse_errata(bytes: &[u8], origin: impl Display) -> Result<ErrataMap> {
+    let json: Value = serde_json::from_slice(bytes).map_err(|err| Json {
+        url: origin.to_string(),
+        desc: "malformed json".to_string(),
+        source: Some(Arc::new(err)),
+    })?;
+    let entries = json
+        .as_array()
+        .ok_or_else(|| DocError::json(&origin, "errata dump isn't a list"))?;
+
+    let mut errata = ErrataMap::new();
+    for entry in entries {
+        if let Some((id, erratum)) = erratum_from_json(&origin, entry)? {
+            errata.entry(id).or_default().push(erratum);
+        }
+    }
+    errata.values_mut().for_each(|errata| errata.sort_by_key(|erratum| erratum.id));
+
+    Ok(errata)
+}
+
+/* read a local copy of the errata dump */
+pub fn load_errata(path: impl AsRef<Path>) -> Result<ErrataMap> {
+    let path = path.as_ref();
+    let bytes = fs::read(path)
+        .map_err(|err| Query(format!("Error reading {}: {}", path.display(), err)))?;
+
+    parse_errata(&bytes, path.display())
+}
+
+/* query the errata dump of the rfc-editor (errata.json next to the errata base url) */
+pub fn fetch_errata() -> Result<ErrataMap> {
+    block_on(fetch_errata_async())
+}
+
+pub fn fetch_errata_with(source: &dyn DocSource) -> Result<ErrataMap> {
+    block_on(fetch_errata_with_async(source))
+}
+
+pub async fn fetch_errata_async() -> Result<ErrataMap> {
+    let source = default_source();
+    fetch_errata_with_async(source.as_ref()).await
+}
+
+pub async fn fetch_errata_with_async(source: &dyn DocSource) -> Result<ErrataMap> {
+    let url = url_config().errata.join("errata.json")?;
+    let resp = source.get(&url).await?;
+
+    parse_errata(resp.bytes(), resp.url())
+}
+
+impl<C> IetfDoc<C>
+    where
+        C: IdContainer,
+{
+    /* attach the errata of this document, none if it isn't in the errata
+     * returns the number of errata */
+    pub fn update_errata(&mut self, errata: &ErrataMap) -> usize {
+        let errata = errata.get(&self.summary.id).cloned().unwrap_or_default();
+        let count = errata.len();
+        self.errata = Some(errata);
+
+        count
+    }
+
+    /* number of errata with the given status, None if the errata weren't queried */
+    pub fn errata_count(&self, status: ErratumStatus) -> Option<usize> {
+        let errata = self.errata.as_ref()?;
+        Some(errata.iter().filter(|erratum| erratum.status == status).count())
+    }
+}
@@ -1,13 +1,59 @@
 use std::error::Error;
 use std::fmt::{Debug, Display, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
 use DocError::*;
 
 pub type Result<T> = std::result::Result<T, DocError>;
+
+/* underlying error (reqwest, serde_json, io...) of a DocError */
+pub type ErrorSource = Option<Arc<dyn Error + Send + Sync>>;
+
 pub enum DocError {
     Url(String),
     Query(String),
     Lookup(String),
     UnknownMeta(String),
+    // name which isn't an rfc, a draft or a subseries
+    Identifier(String),
+    // server answered with an error status, possibly asking to retry after some delay
+    Status {
+        url: String,
+        status: u16,
+        retry_after: Option<Duration>,
+        source: ErrorSource,
+    },
+    // document doesn't exist (404, redirected to the datatracker search, missing fixture)
+    NotFound {
+        url: String,
+        source: ErrorSource,
+    },
+    // page doesn't have the expected layout, selector is the one which didn't match
+    Layout {
+        url: String,
+        selector: String,
+        source: ErrorSource,
+    },
+    // api answered with unexpected json
+    Json {
+        url: String,
+        desc: String,
+        source: ErrorSource,
+    },
+    // document xml isn't well-formed
+    Xml {
+        url: String,
+        source: ErrorSource,
+    },
+    Timeout {
+        url: String,
+        source: ErrorSource,
+    },
+    // could not reach the server
+    Connection {
+        url: String,
+        source: ErrorSource,
+    },
 }
 
 impl DocError {
@@ -17,12 +63,86 @@ impl DocError {
             Query(_) => "QueryError",
             Lookup(_) => "LookupError",
             UnknownMeta(_) => "UnknownMetaError",
+            Identifier(_) => "IdentifierError",
+            Status { .. } => "StatusError",
+            NotFound { .. } => "NotFoundError",
+            Layout { .. } => "LayoutError",
+            Json { .. } => "JsonError",
+            Xml { .. } => "XmlError",
+            Timeout { .. } => "TimeoutError",
+            Connection { .. } => "ConnectionError",
+        }
+    }
+
+    fn description(&self) -> String {
+        let with_source = |desc: String, source: &ErrorSource| match source {
+            Some(source) => format!("{}: {}", desc, source),
+            None => desc,
+        };
+
+        match self {
+            UnknownMeta(s) | Identifier(s) | Lookup(s) | Query(s) | Url(s) => s.clone(),
+            Status { url, status, source, .. } => {
+                with_source(format!("Error querying {}: status {}", url, status), source)
+            }
+            NotFound { url, source } => {
+                with_source(format!("Error querying {}: document doesn't exist", url), source)
+            }
+            Layout { url, selector, source } => with_source(
+                format!("Error parsing {}: nothing matches '{}'", url, selector),
+                source,
+            ),
+            Json { url, desc, source } => {
+                with_source(format!("Error decoding {}: {}", url, desc), source)
+            }
+            Xml { url, source } => with_source(format!("Error parsing {}: malformed xml", url), source),
+            Timeout { url, source } => {
+                with_source(format!("Error querying {}: timed out", url), source)
+            }
+            Connection { url, source } => {
+                with_source(format!("Error querying {}: could not connect", url), source)
+            }
+        }
+    }
+
+    /* whether the same query may succeed if attempted again later */
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Status { status, .. } => matches!(status, 408 | 425 | 429 | 500..=599),
+            Timeout { .. } | Connection { .. } => true,
+            Url(_)
+            | Query(_)
+            | Lookup(_)
+            | UnknownMeta(_)
+            | Identifier(_)
+            | NotFound { .. }
+            | Layout { .. }
+            | Json { .. }
+            | Xml { .. } => false,
         }
     }
 
-    fn description(&self) -> &str {
+    /* delay asked by the server before retrying (Retry-After header) */
+    pub fn retry_after(&self) -> Option<Duration> {
         match self {
-            UnknownMeta(s) | Lookup(s) | Query(s) | Url(s) => s.as_str(),
+            Status { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    pub fn layout(url: impl Display, selector: impl Into<String>) -> DocError {
+        Layout {
+            url: url.to_string(),
+            selector: selector.into(),
+            source: None,
+        }
+    }
+
+    pub fn json(url: impl Display, desc: impl Into<String>) -> DocError {
+        Json {
+            url: url.to_string(),
+            desc: desc.into(),
+            source: None,
         }
     }
 }
@@ -45,7 +165,20 @@ impl Display for DocError {
     }
 }
 
-impl Error for DocError {}
+impl Error for DocError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Status { source, .. }
+            | NotFound { source, .. }
+            | Layout { source, .. }
+            | Json { source, .. }
+            | Xml { source, .. }
+            | Timeout { source, .. }
+            | Connection { source, .. } => source.as_deref().map(|source| source as &(dyn Error + 'static)),
+            Url(_) | Query(_) | Lookup(_) | UnknownMeta(_) | Identifier(_) => None,
+        }
+    }
+}
 
 impl<T> From<DocError> for Result<T> {
     fn from(value: DocError) -> Self {
@@ -61,6 +194,33 @@ impl From<url::ParseError> for DocError {
 
 impl From<reqwest::Error> for DocError {
     fn from(value: reqwest::Error) -> Self {
-        Query(value.to_string())
+        let url = value.url().map(|url| url.to_string()).unwrap_or_default();
+        let status = value.status();
+        let (is_timeout, is_connect, is_decode) = (value.is_timeout(), value.is_connect(), value.is_decode());
+        let source: ErrorSource = Some(Arc::new(value));
+
+        if is_timeout {
+            Timeout { url, source }
+        } else if let Some(status) = status {
+            match status.as_u16() {
+                404 | 410 => NotFound { url, source },
+                status => Status {
+                    url,
+                    status,
+                    retry_after: None,
+                    source,
+                },
+            }
+        } else if is_connect {
+            Connection { url, source }
+        } else if is_decode {
+            Json {
+                url,
+                desc: "malformed response".to_string(),
+                source,
+            }
+        } else {
+            Query(source.map(|source| source.to_string()).unwrap_or_default())
+        }
     }
 }
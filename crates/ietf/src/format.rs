@@ -0,0 +1,89 @@
+use crate::error::Result;
+use crate::source::{block_on, default_source, DocSource};
+use crate::{IdContainer, IetfDoc};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+use std::str::FromStr;
+
+/* Formats the archives publish a document in, named by their extension
+ * rfcs before RFC 8650 have no xml nor pdf, drafts are published in the formats their authors submitted */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum DocFormat {
+    #[default]
+    #[serde(rename = "txt")]
+    Text,
+    #[serde(rename = "html")]
+    Html,
+    #[serde(rename = "xml")]
+    Xml,
+    #[serde(rename = "pdf")]
+    Pdf,
+}
+
+impl DocFormat {
+    pub const ALL: [DocFormat; 4] = [DocFormat::Text, DocFormat::Html, DocFormat::Xml, DocFormat::Pdf];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DocFormat::Text => "txt",
+            DocFormat::Html => "html",
+            DocFormat::Xml => "xml",
+            DocFormat::Pdf => "pdf",
+        }
+    }
+
+    /* pdf is the only binary format */
+    pub fn is_text(&self) -> bool {
+        !matches!(self, DocFormat::Pdf)
+    }
+}
+
+impl Display for DocFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.extension())
+    }
+}
+
+impl FromStr for DocFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let name = s.trim().trim_start_matches('.').to_lowercase();
+        DocFormat::ALL
+            .into_iter()
+            .find(|format| format.extension() == name || (name == "text" && *format == DocFormat::Text))
+            .ok_or_else(|| format!("unknown format '{}', expected one of txt, html, xml, pdf", s))
+    }
+}
+
+impl<C> IetfDoc<C>
+    where
+        C: IdContainer,
+{
+    pub fn download_format(&self, format: DocFormat) -> Result<Vec<u8>> {
+        block_on(self.download_format_async(format))
+    }
+
+    pub fn download_format_with(&self, source: &dyn DocSource, format: DocFormat) -> Result<Vec<u8>> {
+        block_on(self.download_format_with_async(source, format))
+    }
+
+    // like download_raw_async, the futures don't borrow the document
+    pub fn download_format_async(&self, format: DocFormat) -> impl Future<Output = Result<Vec<u8>>> + Send {
+        let url = self.summary.url.format(self.summary.is_rfc, format);
+        async move {
+            let source = default_source();
+            Ok(source.get(&url?).await?.bytes().to_vec())
+        }
+    }
+
+    pub fn download_format_with_async<'a>(
+        &self,
+        source: &'a dyn DocSource,
+        format: DocFormat,
+    ) -> impl Future<Output = Result<Vec<u8>>> + Send + 'a {
+        let url = self.summary.url.format(self.summary.is_rfc, format);
+        async move { Ok(source.get(&url?).await?.bytes().to_vec()) }
+    }
+}
@@ -0,0 +1,210 @@
+use crate::error::{DocError::*, Result};
+use crate::retry::{parse_retry_after, throttle};
+use crate::source::{
+    content_validators, Conditional, ConditionalFuture, DocSource, SourceFuture, SourceResponse, Validators,
+};
+use reqwest::header::{
+    HeaderMap, HeaderName, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, RETRY_AFTER,
+};
+use reqwest::{Certificate, Client, Proxy, StatusCode};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use url::Url;
+
+/* Configuration of the HTTP client shared by every query of an HttpSource
+ * proxies of the environment (HTTP_PROXY, HTTPS_PROXY, NO_PROXY) are used when no proxy is given */
+#[derive(Debug, Clone)]
+pub struct HttpConfig {
+    // timeout of a whole query, None for no timeout
+    pub timeout: Option<Duration>,
+    pub connect_timeout: Option<Duration>,
+    pub user_agent: String,
+    // proxy used for every query (eg: http://proxy.corp:3128)
+    pub proxy: Option<String>,
+    // certificates (PEM or DER files) trusted in addition to the system ones
+    pub root_certificates: Vec<PathBuf>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Some(Duration::from_secs(30)),
+            connect_timeout: Some(Duration::from_secs(10)),
+            user_agent: format!("rfc-dep/{}", env!("CARGO_PKG_VERSION")),
+            proxy: None,
+            root_certificates: Vec::new(),
+        }
+    }
+}
+
+impl HttpConfig {
+    fn certificate(path: &PathBuf) -> Result<Certificate> {
+        let bytes = fs::read(path).map_err(|err| {
+            Query(format!("Error reading certificate {}: {}", path.display(), err))
+        })?;
+
+        Certificate::from_pem(&bytes)
+            .or_else(|_| Certificate::from_der(&bytes))
+            .map_err(|err| Query(format!("Error loading certificate {}: {}", path.display(), err)))
+    }
+
+    pub fn build(&self) -> Result<Client> {
+        let mut builder = Client::builder().user_agent(&self.user_agent);
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(connect_timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        if let Some(ref proxy) = self.proxy {
+            let proxy = Proxy::all(proxy)
+                .map_err(|err| Query(format!("Error configuring proxy {}: {}", proxy, err)))?;
+            builder = builder.proxy(proxy);
+        }
+        for path in &self.root_certificates {
+            builder = builder.add_root_certificate(Self::certificate(path)?);
+        }
+
+        builder
+            .build()
+            .map_err(|err| Query(format!("Error building the http client: {}", err)))
+    }
+}
+
+/* Fetch documents over HTTP(S) or from local file:// urls
+ * the client (and its connection pool) is shared by the clones of the source */
+#[derive(Debug, Clone)]
+pub struct HttpSource {
+    client: Client,
+}
+
+/* client built from the default HttpConfig */
+impl Default for HttpSource {
+    fn default() -> Self {
+        Self::new(&HttpConfig::default()).unwrap_or_else(|_| Self {
+            client: Client::new(),
+        })
+    }
+}
+
+impl HttpSource {
+    pub fn new(config: &HttpConfig) -> Result<Self> {
+        Ok(Self {
+            client: config.build()?,
+        })
+    }
+
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+}
+
+/* file:// urls point to a local directory (eg: a mirror of the archives), directories are read as their index.html */
+fn read_file(url: &Url) -> Result<SourceResponse> {
+    let mut path = url
+        .to_file_path()
+        .map_err(|_| Url(format!("wrong file url {}", url)))?;
+    if path.is_dir() {
+        path.push("index.html");
+    }
+
+    match fs::read(&path) {
+        Ok(body) => {
+            let validators = content_validators(&body);
+            Ok(SourceResponse::new(url.clone(), body).with_validators(validators))
+        }
+        Err(err) if err.kind() == ErrorKind::NotFound => NotFound {
+            url: url.to_string(),
+            source: Some(Arc::new(err)),
+        }
+        .into(),
+        Err(err) => Query(format!("Error reading {}: {}", path.display(), err)).into(),
+    }
+}
+
+/* header value as a string, None when missing or not visible ascii */
+fn header(headers: &HeaderMap, name: HeaderName) -> Option<String> {
+    headers.get(name).and_then(|value| value.to_str().ok()).map(str::to_string)
+}
+
+impl HttpSource {
+    /* query a document, conditionally when validators are given */
+    async fn query(&self, url: &Url, validators: Option<&Validators>) -> Result<Conditional> {
+        if url.scheme() == "file" {
+            let resp = read_file(url)?;
+            return match validators {
+                Some(validators) if validators.etag.is_some() && resp.validators().etag == validators.etag => {
+                    Ok(Conditional::NotModified)
+                }
+                _ => Ok(Conditional::Modified(resp)),
+            };
+        }
+
+        throttle().await;
+        let mut request = self.client.get(url.clone());
+        if let Some(validators) = validators {
+            if let Some(ref etag) = validators.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(ref last_modified) = validators.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let resp = request.send().await?;
+        let status_code = resp.status();
+        if status_code == StatusCode::NOT_MODIFIED {
+            return Ok(Conditional::NotModified);
+        }
+        if status_code == StatusCode::NOT_FOUND || status_code == StatusCode::GONE {
+            return NotFound {
+                url: resp.url().to_string(),
+                source: None,
+            }
+            .into();
+        }
+        if !StatusCode::is_success(&status_code) {
+            let retry_after = resp
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(parse_retry_after);
+
+            return Status {
+                url: resp.url().to_string(),
+                status: status_code.as_u16(),
+                retry_after,
+                source: None,
+            }
+            .into();
+        }
+
+        let url = resp.url().clone();
+        let validators = Validators {
+            etag: header(resp.headers(), ETAG),
+            last_modified: header(resp.headers(), LAST_MODIFIED),
+        };
+        let body = resp.bytes().await?.to_vec();
+
+        Ok(Conditional::Modified(SourceResponse::new(url, body).with_validators(validators)))
+    }
+}
+
+impl DocSource for HttpSource {
+    fn get<'a>(&'a self, url: &'a Url) -> SourceFuture<'a> {
+        Box::pin(async move {
+            match self.query(url, None).await? {
+                Conditional::Modified(resp) => Ok(resp),
+                // no validators were sent
+                Conditional::NotModified => Query(format!("Error querying {}: unexpected 304 Not Modified", url)).into(),
+            }
+        })
+    }
+
+    fn get_if_modified<'a>(&'a self, url: &'a Url, validators: &'a Validators) -> ConditionalFuture<'a> {
+        Box::pin(self.query(url, Some(validators)))
+    }
+}
@@ -0,0 +1,260 @@
+use crate::error::{DocError, Result};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/* Identify IETF documents: rfcs, drafts (with or without revision) and subseries
+ * the canonical form (Display) is the datatracker name: rfc9293, draft-ietf-tcpm-rfc793bis-28, bcp14 */
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum DocIdentifier {
+    Rfc(u32),
+    // name is the full draft name without revision (draft-ietf-tcpm-rfc793bis)
+    Draft { name: String, revision: Option<u8> },
+    Bcp(u32),
+    Std(u32),
+    Fyi(u32),
+}
+
+impl DocIdentifier {
+    pub fn draft(name: impl Into<String>, revision: Option<u8>) -> DocIdentifier {
+        DocIdentifier::Draft {
+            name: name.into().to_lowercase(),
+            revision,
+        }
+    }
+
+    pub fn is_rfc(&self) -> bool {
+        matches!(self, DocIdentifier::Rfc(_))
+    }
+
+    pub fn is_draft(&self) -> bool {
+        matches!(self, DocIdentifier::Draft { .. })
+    }
+
+    pub fn is_subseries(&self) -> bool {
+        matches!(self, DocIdentifier::Bcp(_) | DocIdentifier::Std(_) | DocIdentifier::Fyi(_))
+    }
+
+    /* "03" for draft-foo-03, None for other documents and drafts without revision */
+    pub fn revision(&self) -> Option<String> {
+        match self {
+            DocIdentifier::Draft {
+                revision: Some(revision),
+                ..
+            } => Some(format!("{:02}", revision)),
+            _ => None,
+        }
+    }
+
+    /* the document whatever its revision: draft-foo-03 => draft-foo, other documents are unchanged */
+    pub fn unversioned(&self) -> DocIdentifier {
+        match self {
+            DocIdentifier::Draft { name, .. } => DocIdentifier::Draft {
+                name: name.clone(),
+                revision: None,
+            },
+            other => other.clone(),
+        }
+    }
+
+    /* the given revision ("03") of a draft, None for other documents or a wrong revision */
+    pub fn with_revision(&self, revision: &str) -> Option<DocIdentifier> {
+        match self {
+            DocIdentifier::Draft { name, .. } => Some(DocIdentifier::Draft {
+                name: name.clone(),
+                revision: Some(revision.trim().parse().ok()?),
+            }),
+            _ => None,
+        }
+    }
+
+    /* whether both identify the same document, revisions of a draft being the same document */
+    pub fn same_document(&self, other: &DocIdentifier) -> bool {
+        self.unversioned() == other.unversioned()
+    }
+
+    /* name of a subseries or an rfc number: ("rfc", "0793") => rfc793 */
+    pub fn from_number(prefix: &str, number: &str) -> Option<DocIdentifier> {
+        let number = number.trim().parse::<u32>().ok().filter(|number| *number > 0)?;
+        match prefix.to_lowercase().as_str() {
+            "rfc" => Some(DocIdentifier::Rfc(number)),
+            "bcp" => Some(DocIdentifier::Bcp(number)),
+            "std" => Some(DocIdentifier::Std(number)),
+            "fyi" => Some(DocIdentifier::Fyi(number)),
+            _ => None,
+        }
+    }
+}
+
+impl Display for DocIdentifier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DocIdentifier::Rfc(number) => write!(f, "rfc{}", number),
+            DocIdentifier::Draft {
+                name,
+                revision: Some(revision),
+            } => write!(f, "{}-{:02}", name, revision),
+            DocIdentifier::Draft { name, revision: None } => write!(f, "{}", name),
+            DocIdentifier::Bcp(number) => write!(f, "bcp{}", number),
+            DocIdentifier::Std(number) => write!(f, "std{}", number),
+            DocIdentifier::Fyi(number) => write!(f, "fyi{}", number),
+        }
+    }
+}
+
+/* accepts any case, spaces and zero padding: "RFC 0793", "rfc793", "BCP14", "draft-foo-bar-03", "draft-foo-bar" */
+impl FromStr for DocIdentifier {
+    type Err = DocError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let name = s.replace(' ', "").to_lowercase();
+        let wrong_id = || DocError::Identifier(format!("wrong document identifier '{}'", s.trim()));
+
+        if let Some(draft) = name.strip_prefix("draft-") {
+            if draft.is_empty()
+                || !draft
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.' || c == '_')
+            {
+                return Err(wrong_id());
+            }
+
+            // a two digits last part is the revision
+            return Ok(match name.rsplit_once('-') {
+                Some((base, revision))
+                    if revision.len() == 2 && revision.chars().all(|c| c.is_ascii_digit()) =>
+                {
+                    DocIdentifier::Draft {
+                        name: base.to_string(),
+                        revision: revision.parse().ok(),
+                    }
+                }
+                _ => DocIdentifier::Draft {
+                    name,
+                    revision: None,
+                },
+            });
+        }
+
+        let split = name.find(|c: char| c.is_ascii_digit()).ok_or_else(wrong_id)?;
+        let (prefix, number) = name.split_at(split);
+        DocIdentifier::from_number(prefix.trim_end_matches('-'), number).ok_or_else(wrong_id)
+    }
+}
+
+impl TryFrom<&str> for DocIdentifier {
+    type Error = DocError;
+
+    fn try_from(value: &str) -> Result<Self> {
+        value.parse()
+    }
+}
+
+impl TryFrom<String> for DocIdentifier {
+    type Error = DocError;
+
+    fn try_from(value: String) -> Result<Self> {
+        value.parse()
+    }
+}
+
+/* (de)serialized as its canonical name so that it can be a json map key */
+impl Serialize for DocIdentifier {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for DocIdentifier {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn draft(name: &str, revision: Option<u8>) -> DocIdentifier {
+        DocIdentifier::draft(name, revision)
+    }
+
+    /* (canonical name, identifier) */
+    fn canonical() -> Vec<(&'static str, DocIdentifier)> {
+        vec![
+            ("rfc9293", DocIdentifier::Rfc(9293)),
+            ("draft-ietf-tcpm-rfc793bis-28", draft("draft-ietf-tcpm-rfc793bis", Some(28))),
+            ("draft-ietf-tcpm-rfc793bis-00", draft("draft-ietf-tcpm-rfc793bis", Some(0))),
+            ("draft-ietf-tcpm-rfc793bis", draft("draft-ietf-tcpm-rfc793bis", None)),
+            ("bcp14", DocIdentifier::Bcp(14)),
+            ("std7", DocIdentifier::Std(7)),
+            ("fyi36", DocIdentifier::Fyi(36)),
+        ]
+    }
+
+    #[test]
+    fn display_from_str_round_trip() {
+        for (name, id) in canonical() {
+            assert_eq!(name.parse::<DocIdentifier>().unwrap(), id);
+            assert_eq!(id.to_string(), name);
+            assert_eq!(id.to_string().parse::<DocIdentifier>().unwrap(), id);
+        }
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        for (name, id) in canonical() {
+            let json = serde_json::to_string(&id).unwrap();
+            assert_eq!(json, format!("\"{}\"", name));
+            assert_eq!(serde_json::from_str::<DocIdentifier>(&json).unwrap(), id);
+        }
+
+        // map keys (projects are keyed by document)
+        let map = BTreeMap::from([(DocIdentifier::Rfc(793), 1), (draft("draft-foo-bar", Some(3)), 2)]);
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(json, r#"{"rfc793":1,"draft-foo-bar-03":2}"#);
+        assert_eq!(serde_json::from_str::<BTreeMap<DocIdentifier, i32>>(&json).unwrap(), map);
+    }
+
+    #[test]
+    fn lenient_names() {
+        for (name, id) in [
+            ("RFC 0793", DocIdentifier::Rfc(793)),
+            ("rfc-793", DocIdentifier::Rfc(793)),
+            (" BCP14 ", DocIdentifier::Bcp(14)),
+            ("STD 0007", DocIdentifier::Std(7)),
+            ("Draft-Foo-Bar-03", draft("draft-foo-bar", Some(3))),
+            // only a two digits last part is a revision
+            ("draft-foo-bar-3", draft("draft-foo-bar-3", None)),
+            ("draft-foo-bar-2023", draft("draft-foo-bar-2023", None)),
+        ] {
+            assert_eq!(name.parse::<DocIdentifier>().unwrap(), id, "{:?}", name);
+        }
+    }
+
+    #[test]
+    fn invalid_names() {
+        for name in ["", "rfc", "rfc0", "rfcx", "793", "xyz12", "draft-", "draft-foo/bar", "bcp-"] {
+            assert!(
+                matches!(name.parse::<DocIdentifier>(), Err(DocError::Identifier(_))),
+                "{:?}",
+                name
+            );
+            assert!(serde_json::from_str::<DocIdentifier>(&format!("\"{}\"", name)).is_err());
+        }
+        assert!(serde_json::from_str::<DocIdentifier>("793").is_err());
+    }
+
+    #[test]
+    fn revisions() {
+        let id = draft("draft-foo-bar", Some(3));
+        assert_eq!(id.revision().as_deref(), Some("03"));
+        assert_eq!(id.unversioned(), draft("draft-foo-bar", None));
+        assert_eq!(id.unversioned().with_revision("04"), Some(draft("draft-foo-bar", Some(4))));
+        assert!(id.same_document(&draft("draft-foo-bar", Some(5))));
+        assert_eq!(DocIdentifier::Rfc(1).with_revision("01"), None);
+    }
+}
@@ -0,0 +1,266 @@
+use crate::error::{DocError::*, Result};
+use crate::meta::Meta;
+use crate::doc::SummaryDetails;
+use crate::subseries::subseries_title;
+use crate::{DocIdentifier, IdContainer, IetfDoc, MetaMap, Summary};
+use fast_xml::events::Event;
+use fast_xml::Reader;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+const MONTHS: [&str; 12] = [
+    "january", "february", "march", "april", "may", "june",
+    "july", "august", "september", "october", "november", "december",
+];
+
+/* YYYY, YYYY-MM or YYYY-MM-DD from an english month name or number (april 1st rfcs have a day) */
+pub(crate) fn format_date(year: &str, month: Option<&str>, day: Option<&str>) -> String {
+    let month = month.and_then(|month| {
+        MONTHS
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(month.trim()))
+            .or_else(|| {
+                month
+                    .trim()
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|month| (1..=12).contains(month))
+                    .map(|month| month - 1)
+            })
+    });
+
+    match (month, day) {
+        (Some(month), Some(day)) => format!("{}-{:02}-{:0>2}", year.trim(), month + 1, day.trim()),
+        (Some(month), None) => format!("{}-{:02}", year.trim(), month + 1),
+        (None, _) => year.trim().to_string(),
+    }
+}
+
+/* entries of the index: rfcs and subseries */
+const ENTRIES: [&str; 4] = ["rfc-entry", "bcp-entry", "std-entry", "fyi-entry"];
+
+/* an <rfc-entry> (or a subseries entry) being parsed */
+#[derive(Debug, Default)]
+struct IndexEntry {
+    id: Option<DocIdentifier>,
+    title: String,
+    status: Option<String>,
+    draft: Option<String>,
+    year: Option<String>,
+    month: Option<String>,
+    day: Option<String>,
+    authors: Vec<String>,
+    pages: Option<u32>,
+    stream: Option<String>,
+    group: Option<String>,
+    abstract_text: Vec<String>,
+    obsoletes: Vec<DocIdentifier>,
+    obsoleted_by: Vec<DocIdentifier>,
+    updates: Vec<DocIdentifier>,
+    updated_by: Vec<DocIdentifier>,
+    is_also: Vec<DocIdentifier>,
+}
+
+/* index doc-ids are zero padded: RFC0793, BCP0014, STD0007 */
+fn index_id(doc_id: &str) -> Option<DocIdentifier> {
+    let split = doc_id.find(|c: char| c.is_ascii_digit())?;
+    let (prefix, number) = doc_id.split_at(split);
+    match prefix {
+        "RFC" | "BCP" | "STD" | "FYI" => DocIdentifier::from_number(prefix, number),
+        _ => None,
+    }
+}
+
+impl IndexEntry {
+    fn push_text(&mut self, path: &[String], text: String) {
+        let path: Vec<&str> = path.iter().map(String::as_str).collect();
+        match path[..] {
+            [.., "rfc-entry", field] => match field {
+                "doc-id" => self.id = index_id(&text),
+                "title" => self.title = text,
+                "current-status" => self.status = Some(text),
+                "draft" => self.draft = Some(text),
+                "stream" => self.stream = Some(text),
+                "page-count" => self.pages = text.trim().parse().ok(),
+                // rfcs outside of any working group have "NON WORKING GROUP"
+                "wg_acronym" if !text.contains(' ') => self.group = Some(text),
+                _ => {}
+            },
+            [.., "rfc-entry", "date", field] => match field {
+                "year" => self.year = Some(text),
+                "month" => self.month = Some(text),
+                "day" => self.day = Some(text),
+                _ => {}
+            },
+            [.., "rfc-entry", "author", "name"] => self.authors.push(text),
+            // older indexes have the page count in <format>
+            [.., "rfc-entry", "format", "page-count"] => self.pages = text.trim().parse().ok(),
+            [.., "rfc-entry", "abstract", "p"] => self.abstract_text.push(text),
+            // subseries list their rfcs in <is-also>, std entries have a title
+            [.., "bcp-entry" | "std-entry" | "fyi-entry", field] => match field {
+                "doc-id" => self.id = index_id(&text),
+                "title" => self.title = text,
+                _ => {}
+            },
+            [.., "bcp-entry" | "std-entry" | "fyi-entry", "is-also", "doc-id"] => self.is_also.extend(index_id(&text)),
+            [.., "rfc-entry", relation, "doc-id"] => {
+                let list = match relation {
+                    "obsoletes" => &mut self.obsoletes,
+                    "obsoleted-by" => &mut self.obsoleted_by,
+                    "updates" => &mut self.updates,
+                    "updated-by" => &mut self.updated_by,
+                    "is-also" => &mut self.is_also,
+                    _ => return,
+                };
+                list.extend(index_id(&text));
+            }
+            _ => {}
+        }
+    }
+
+    fn date(&self) -> Option<String> {
+        let year = self.year.as_ref()?;
+        Some(format_date(year, self.month.as_deref(), self.day.as_deref()))
+    }
+
+    fn into_subseries<C: IdContainer>(self, id: DocIdentifier) -> Result<IetfDoc<C>> {
+        let title = match self.title.trim() {
+            "" => subseries_title(&id),
+            title => title.to_string(),
+        };
+        let summary = Summary::new(id, String::new(), false, title)?;
+
+        let mut meta: MetaMap<C> = MetaMap::default();
+        if !self.is_also.is_empty() {
+            meta.push_meta(Meta::Contains(self.is_also.into_iter().map(C::Holder::from).collect()));
+        }
+
+        Ok(IetfDoc {
+            summary,
+            meta,
+            revisions: Vec::new(),
+            errata: None,
+        })
+    }
+
+    fn into_doc<C: IdContainer>(self) -> Result<Option<IetfDoc<C>>> {
+        let id = match self.id.clone() {
+            Some(id) => id,
+            None => return Ok(None),
+        };
+        if id.is_subseries() {
+            return self.into_subseries(id).map(Some);
+        }
+
+        // revision of the draft the rfc was published from, as on the datatracker
+        let revision = self
+            .draft
+            .as_ref()
+            .and_then(|draft| draft.rsplit_once('-'))
+            .map(|(_, rev)| rev)
+            .filter(|rev| rev.len() == 2 && rev.chars().all(|c| c.is_ascii_digit()))
+            .unwrap_or("00")
+            .to_string();
+
+        let mut summary = Summary::new(id, revision, true, self.title.clone())?;
+        summary.complete(SummaryDetails {
+            status: self.status.clone(),
+            date: self.date(),
+            authors: self.authors,
+            stream: self.stream,
+            group: self.group,
+            pages: self.pages,
+            abstract_text: Some(self.abstract_text.join("\n\n")).filter(|text| !text.is_empty()),
+            ..SummaryDetails::default()
+        });
+
+        let mut meta: MetaMap<C> = MetaMap::default();
+        let to_set = |ids: Vec<DocIdentifier>| -> HashSet<C::Holder<DocIdentifier>> {
+            ids.into_iter().map(C::Holder::from).collect()
+        };
+        for (ids, make) in [
+            (self.obsoletes, Meta::Obsoletes as fn(_) -> _),
+            (self.obsoleted_by, Meta::ObsoletedBy),
+            (self.updates, Meta::Updates),
+            (self.updated_by, Meta::UpdatedBy),
+        ] {
+            if !ids.is_empty() {
+                meta.push_meta(make(to_set(ids)));
+            }
+        }
+        // an rfc can be part of several subseries
+        if !self.is_also.is_empty() {
+            meta.push_meta(Meta::AlsoKnownAs(to_set(self.is_also)));
+        }
+
+        Ok(Some(IetfDoc {
+            summary,
+            meta,
+            revisions: Vec::new(),
+            errata: None,
+        }))
+    }
+}
+
+/* parse the rfc and subseries (bcp, std, fyi) entries of the RFC Editor index (https://www.rfc-editor.org/rfc-index.xml)
+ * not issued rfcs are skipped */
+pub fn parse_rfc_index<C: IdContainer>(bytes: &[u8]) -> Result<Vec<IetfDoc<C>>> {
+    let mut xml = Reader::from_bytes(bytes);
+    xml.trim_text(true);
+    let mut buf = Vec::new();
+
+    let mut docs = Vec::new();
+    let mut path: Vec<String> = Vec::new();
+    let mut entry: Option<IndexEntry> = None;
+
+    loop {
+        match xml.read_event(&mut buf) {
+            Ok(Event::Start(ref e)) => {
+                let name = String::from_utf8_lossy(e.local_name()).to_string();
+                if ENTRIES.contains(&name.as_str()) {
+                    entry = Some(IndexEntry::default());
+                }
+                path.push(name);
+            }
+            Ok(Event::End(ref e)) => {
+                path.pop();
+                if ENTRIES.iter().any(|entry| entry.as_bytes() == e.local_name()) {
+                    if let Some(doc) = entry.take().map(IndexEntry::into_doc).transpose()?.flatten() {
+                        docs.push(doc);
+                    }
+                }
+            }
+            Ok(Event::Text(ref e)) => {
+                if let Some(entry) = entry.as_mut() {
+                    let text = e
+                        .unescape_and_decode(&xml)
+                        .map_err(|err| Query(format!("Error decoding rfc index: {}", err)))?;
+                    entry.push_text(&path, text);
+                }
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(err) => {
+                return Query(format!(
+                    "Error parsing rfc index at {}: {}",
+                    xml.buffer_position(),
+                    err
+                ))
+                .into()
+            }
+        }
+        buf.clear();
+    }
+
+    Ok(docs)
+}
+
+/* load a local copy of the RFC Editor index */
+pub fn load_rfc_index<C: IdContainer>(path: impl AsRef<Path>) -> Result<Vec<IetfDoc<C>>> {
+    let path = path.as_ref();
+    let bytes = fs::read(path)
+        .map_err(|err| Query(format!("Error reading {}: {}", path.display(), err)))?;
+
+    parse_rfc_index(&bytes)
+}
@@ -1,8 +1,33 @@
 mod doc;
 pub mod error;
+mod errata;
+mod format;
+mod http;
+mod id;
+mod index;
+mod lookup;
 mod meta;
+mod refresh;
+mod retry;
+mod revision;
+mod source;
+mod subseries;
+mod text;
 mod url;
+mod xml;
 
-pub use doc::DocIdentifier;
+pub use id::DocIdentifier;
 pub use doc::*;
+pub use errata::*;
+pub use format::*;
+pub use http::*;
+pub use index::*;
+pub use lookup::*;
 pub use meta::*;
+pub use refresh::*;
+pub use retry::*;
+pub use revision::*;
+pub use source::*;
+pub use subseries::*;
+pub use text::*;
+pub use crate::url::{set_url_config, url_config, UrlConfig};
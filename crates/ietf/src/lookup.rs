@@ -0,0 +1,440 @@
+use crate::error::{DocError, DocError::*, Result};
+use crate::source::{block_on, default_source, DocSource};
+use crate::doc::SummaryDetails;
+use crate::url::url_config;
+use crate::{DocIdentifier, IdContainer, IetfDoc, Summary};
+use rayon::prelude::*;
+use serde_json::Value;
+use std::collections::HashMap;
+use url::Url;
+
+// number of documents asked per page
+const PAGE_SIZE: usize = 100;
+
+/* Query of the datatracker document api (Tastypie), filters are combined
+ * the pages of results are followed (meta.next) until the limit is reached */
+#[derive(Debug, Clone)]
+pub struct LookupQuery {
+    title: Option<String>,
+    name_prefix: Option<String>,
+    group: Option<String>,
+    area: Option<String>,
+    stream: Option<String>,
+    author: Option<String>,
+    status: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+    include_drafts: bool,
+    limit: Option<usize>,
+    page_size: usize,
+}
+
+impl Default for LookupQuery {
+    fn default() -> Self {
+        Self {
+            title: None,
+            name_prefix: None,
+            group: None,
+            area: None,
+            stream: None,
+            author: None,
+            status: None,
+            since: None,
+            until: None,
+            include_drafts: false,
+            limit: None,
+            page_size: PAGE_SIZE,
+        }
+    }
+}
+
+impl LookupQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /* title contains (case insensitive) */
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /* document name starts with, eg: "draft-ietf-tcpm-" */
+    pub fn name_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.name_prefix = Some(prefix.into());
+        self
+    }
+
+    /* working group acronym, eg: "tcpm" */
+    pub fn group(mut self, group: impl Into<String>) -> Self {
+        self.group = Some(group.into());
+        self
+    }
+
+    /* area acronym of the working group, eg: "wit" */
+    pub fn area(mut self, area: impl Into<String>) -> Self {
+        self.area = Some(area.into());
+        self
+    }
+
+    /* stream slug: "ietf", "irtf", "iab", "ise" */
+    pub fn stream(mut self, stream: impl Into<String>) -> Self {
+        self.stream = Some(stream.into());
+        self
+    }
+
+    /* author name contains (case insensitive) */
+    pub fn author(mut self, author: impl Into<String>) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+
+    /* document state slug: "active", "expired", "rfc", "repl"...
+     * includes drafts since only rfcs are returned by default */
+    pub fn status(mut self, status: impl Into<String>) -> Self {
+        self.status = Some(status.into());
+        self
+    }
+
+    /* last modified on or after (YYYY-MM-DD) */
+    pub fn since(mut self, date: impl Into<String>) -> Self {
+        self.since = Some(date.into());
+        self
+    }
+
+    /* last modified on or before (YYYY-MM-DD) */
+    pub fn until(mut self, date: impl Into<String>) -> Self {
+        self.until = Some(date.into());
+        self
+    }
+
+    /* include drafts which are not published as rfcs (only rfcs by default) */
+    pub fn include_drafts(mut self, include_drafts: bool) -> Self {
+        self.include_drafts = include_drafts;
+        self
+    }
+
+    /* maximum number of results, every page is followed otherwise */
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /* whether no filter is set, datatracker would list every document */
+    pub fn is_empty(&self) -> bool {
+        [
+            &self.title,
+            &self.name_prefix,
+            &self.group,
+            &self.area,
+            &self.stream,
+            &self.author,
+            &self.status,
+            &self.since,
+            &self.until,
+        ]
+        .iter()
+        .all(|filter| filter.as_deref().is_none_or(str::is_empty))
+    }
+
+    /* url of the first page of results, values are url encoded */
+    pub fn url(&self) -> Result<Url> {
+        let mut url = url_config().datatracker.join("api/v1/doc/document/")?;
+        let page_size = match self.limit {
+            Some(limit) => self.page_size.min(limit.max(1)),
+            None => self.page_size,
+        };
+
+        {
+            let mut pairs = url.query_pairs_mut();
+            for (key, value) in [
+                ("title__icontains", &self.title),
+                ("name__startswith", &self.name_prefix),
+                ("group__acronym", &self.group),
+                ("group__parent__acronym", &self.area),
+                ("stream__slug", &self.stream),
+                ("documentauthor__person__name__icontains", &self.author),
+                ("states__slug", &self.status),
+                ("time__gte", &self.since),
+                ("time__lte", &self.until),
+            ] {
+                if let Some(value) = value.as_deref().filter(|value| !value.is_empty()) {
+                    pairs.append_pair(key, value);
+                }
+            }
+
+            pairs
+                .append_pair("limit", &page_size.to_string())
+                .append_pair("offset", "0")
+                .append_pair("format", "json");
+            // state 3 of drafts is "RFC", useless when filtering by state
+            if !self.include_drafts && self.status.is_none() {
+                pairs.append_pair("states__in", "3");
+            }
+            pairs.append_pair("type__in", "draft");
+        }
+
+        Ok(url)
+    }
+
+    /* pages of results fetched one at a time from the source */
+    pub fn pages<'a>(&self, source: &'a dyn DocSource) -> Result<LookupPages<'a>> {
+        if self.is_empty() {
+            return Lookup("no query".to_string()).into();
+        }
+
+        Ok(LookupPages {
+            source,
+            next: Some(self.url()?),
+            remaining: self.limit,
+            groups: HashMap::new(),
+            errors: Vec::new(),
+        })
+    }
+}
+
+/* Pages of the results of a LookupQuery, next_page (or iterating) queries the next one */
+#[derive(Debug)]
+pub struct LookupPages<'a> {
+    source: &'a dyn DocSource,
+    next: Option<Url>,
+    remaining: Option<usize>,
+    // acronyms of the group uris already queried
+    groups: HashMap<String, Option<String>>,
+    // malformed results which were skipped, groups which couldn't be queried
+    errors: Vec<DocError>,
+}
+
+impl<'a> LookupPages<'a> {
+    /* None once every page (or the limit) is reached */
+    pub async fn next_page(&mut self) -> Result<Option<Vec<Summary>>> {
+        let url = match self.next.take() {
+            Some(url) if self.remaining != Some(0) => url,
+            _ => return Ok(None),
+        };
+
+        let resp = self.source.get(&url).await?;
+        let mut json = resp.json::<Value>()?;
+
+        // next is relative to the server (eg: "/api/v1/doc/document/?...&offset=100")
+        self.next = match json.pointer("/meta/next") {
+            Some(Value::String(next)) => Some(url.join(next)?),
+            _ => None,
+        };
+
+        let objects = json
+            .get_mut("objects")
+            .and_then(|objects| objects.as_array_mut())
+            .ok_or_else(|| DocError::json(&url, "no 'objects' list"))?;
+
+        let last_page = objects.is_empty();
+
+        // a malformed object is skipped, not the whole page
+        let mut summaries: Vec<(Summary, Option<String>)> = Vec::with_capacity(objects.len());
+        let results: Vec<_> = objects
+            .par_drain(..)
+            .map(|obj| summary_from_json(&url, &obj))
+            .collect();
+        for result in results {
+            match result {
+                Ok(summary) => summaries.push(summary),
+                Err(err) => self.errors.push(err),
+            }
+        }
+
+        if let Some(remaining) = self.remaining.as_mut() {
+            summaries.truncate(*remaining);
+            *remaining -= summaries.len();
+        }
+        let summaries = self.resolve_groups(&url, summaries).await;
+        if last_page {
+            self.next = None;
+        }
+
+        Ok(Some(summaries))
+    }
+
+    /* group acronyms of the summaries, each group is queried once
+     * groups which can't be queried are left unknown */
+    async fn resolve_groups(&mut self, url: &Url, summaries: Vec<(Summary, Option<String>)>) -> Vec<Summary> {
+        let mut resolved = Vec::with_capacity(summaries.len());
+        for (mut summary, group) in summaries {
+            if let Some(uri) = group {
+                if !self.groups.contains_key(&uri) {
+                    let acronym = self.query_group(url, &uri).await;
+                    self.groups.insert(uri.clone(), acronym);
+                }
+                summary.group = self.groups.get(&uri).cloned().flatten();
+            }
+            resolved.push(summary);
+        }
+
+        resolved
+    }
+
+    async fn query_group(&mut self, url: &Url, uri: &str) -> Option<String> {
+        let query = async {
+            let mut group_url = url.join(uri)?;
+            group_url.set_query(Some("format=json"));
+            let json = self.source.get(&group_url).await?.json::<Value>()?;
+
+            Ok::<_, DocError>(json.get("acronym").and_then(Value::as_str).map(str::to_string))
+        };
+
+        match query.await {
+            // individual submissions belong to the "none" group
+            Ok(acronym) => acronym.filter(|acronym| acronym != "none"),
+            Err(err) => {
+                self.errors.push(err);
+                None
+            }
+        }
+    }
+
+    /* errors of the results skipped (malformed objects) or left incomplete (group not found) so far */
+    pub fn errors(&self) -> &[DocError] {
+        &self.errors
+    }
+
+    /* every remaining result */
+    pub async fn collect_all(mut self) -> Result<Vec<Summary>> {
+        let mut summaries = Vec::new();
+        while let Some(page) = self.next_page().await? {
+            summaries.extend(page);
+        }
+
+        Ok(summaries)
+    }
+}
+
+impl Iterator for LookupPages<'_> {
+    type Item = Result<Vec<Summary>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        block_on(self.next_page()).transpose()
+    }
+}
+
+/* last segment of an api resource uri: "/api/v1/name/stdlevelname/ps/" => "ps" */
+fn uri_slug(value: Option<&Value>) -> Option<&str> {
+    value?.as_str()?.rsplit('/').find(|part| !part.is_empty())
+}
+
+fn std_level_name(slug: &str) -> String {
+    match slug {
+        "ps" => "Proposed Standard",
+        "ds" => "Draft Standard",
+        "std" => "Internet Standard",
+        "bcp" => "Best Current Practice",
+        "inf" => "Informational",
+        "exp" => "Experimental",
+        "hist" => "Historic",
+        "unkn" => "Unknown",
+        slug => slug,
+    }
+    .to_string()
+}
+
+/* summary of an object of the datatracker document api
+ * its group is an api uri, see LookupPages::resolve_groups */
+fn summary_from_json(url: &Url, obj: &Value) -> Result<(Summary, Option<String>)> {
+    // strings may be numbers (eg: rfc number)
+    let field = |key: &str| -> Result<String> {
+        match obj.get(key) {
+            Some(Value::String(value)) => Ok(value.clone()),
+            Some(Value::Number(value)) => Ok(value.to_string()),
+            _ => Err(DocError::json(url, format!("missing '{}' in {}", key, obj))),
+        }
+    };
+    // dates are timestamps: "2023-07-04T12:00:00"
+    let date = |key: &str| obj.get(key)?.as_str()?.get(..10).map(str::to_string);
+
+    let rfc_num = obj.get("rfc").filter(|val| !val.is_null());
+    let is_rfc = rfc_num.is_some();
+    let revision = field("rev")?;
+    let id = if is_rfc {
+        format!("rfc{}", field("rfc")?)
+    } else {
+        format!("{}-{}", field("name")?, revision)
+    }
+    .parse::<DocIdentifier>()
+    .map_err(|err| DocError::json(url, err.to_string()))?;
+
+    let mut summary = Summary::new(id, revision, is_rfc, field("title")?)?;
+    summary.complete(SummaryDetails {
+        status: uri_slug(obj.get("std_level"))
+            .or_else(|| uri_slug(obj.get("intended_std_level")))
+            .map(std_level_name),
+        // last update of a draft is the publication of its current revision
+        date: if is_rfc { None } else { date("time") },
+        expires: if is_rfc { None } else { date("expires") },
+        stream: uri_slug(obj.get("stream")).map(str::to_uppercase),
+        pages: obj.get("pages").and_then(Value::as_u64).map(|pages| pages as u32),
+        abstract_text: obj
+            .get("abstract")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .map(str::to_string),
+        ..SummaryDetails::default()
+    });
+    let group = obj.get("group").and_then(Value::as_str).map(str::to_string);
+
+    Ok((summary, group))
+}
+
+impl<C> IetfDoc<C>
+    where
+        C: IdContainer,
+{
+    pub fn lookup_query(query: &LookupQuery) -> Result<Vec<Summary>> {
+        block_on(Self::lookup_query_async(query))
+    }
+
+    pub fn lookup_query_with(source: &dyn DocSource, query: &LookupQuery) -> Result<Vec<Summary>> {
+        block_on(Self::lookup_query_with_async(source, query))
+    }
+
+    pub async fn lookup_query_async(query: &LookupQuery) -> Result<Vec<Summary>> {
+        let source = default_source();
+        Self::lookup_query_with_async(source.as_ref(), query).await
+    }
+
+    pub async fn lookup_query_with_async(source: &dyn DocSource, query: &LookupQuery) -> Result<Vec<Summary>> {
+        query.pages(source)?.collect_all().await
+    }
+
+    /* latest revision ("03") of a draft from the datatracker api, None for other documents */
+    pub fn latest_revision(id: &DocIdentifier) -> Result<Option<String>> {
+        block_on(Self::latest_revision_async(id))
+    }
+
+    pub fn latest_revision_with(source: &dyn DocSource, id: &DocIdentifier) -> Result<Option<String>> {
+        block_on(Self::latest_revision_with_async(source, id))
+    }
+
+    pub async fn latest_revision_async(id: &DocIdentifier) -> Result<Option<String>> {
+        let source = default_source();
+        Self::latest_revision_with_async(source.as_ref(), id).await
+    }
+
+    pub async fn latest_revision_with_async(source: &dyn DocSource, id: &DocIdentifier) -> Result<Option<String>> {
+        let DocIdentifier::Draft { name, .. } = id else {
+            return Ok(None);
+        };
+
+        let mut url = url_config().datatracker.join(&format!("api/v1/doc/document/{}/", name))?;
+        url.set_query(Some("format=json"));
+        let json = source.get(&url).await?.json::<Value>()?;
+
+        match json.get("rev") {
+            Some(Value::String(rev)) => Ok(Some(rev.clone())),
+            _ => Err(DocError::json(&url, "no 'rev'")),
+        }
+    }
+}
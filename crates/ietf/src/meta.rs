@@ -4,7 +4,7 @@ use crate::error::Result;
 use crate::{name_to_id, DocIdentifier};
 use fast_xml::events::attributes::Attribute;
 use serde::de::DeserializeOwned;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::fmt::Debug;
 use std::hash::Hash;
 use variant_map::derive::VariantStore;
@@ -13,6 +13,25 @@ pub trait IdContainer {
     type Holder<T>: Serialize + DeserializeOwned + Send + Debug + Clone + From<DocIdentifier> + Eq + Hash;
 }
 
+/* projects saved when an rfc had a single AlsoKnownAs ("AlsoKnownAs": id) are loaded as a set of one */
+fn one_or_many<'de, D, T>(deserializer: D) -> std::result::Result<HashSet<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: DeserializeOwned + Eq + Hash,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged, bound = "T: DeserializeOwned + Eq + Hash")]
+    enum OneOrMany<T> {
+        Many(HashSet<T>),
+        One(T),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::Many(set) => set,
+        OneOrMany::One(item) => HashSet::from([item]),
+    })
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, VariantStore)]
 #[VariantStore(keys(derive(Clone)), visibility="pub")]
 pub enum Meta<C>
@@ -23,10 +42,17 @@ pub enum Meta<C>
     UpdatedBy(HashSet<C::Holder<DocIdentifier>>),
     Obsoletes(HashSet<C::Holder<DocIdentifier>>),
     ObsoletedBy(HashSet<C::Holder<DocIdentifier>>),
-    AlsoKnownAs(DocIdentifier),
+    // an rfc can be part of several subseries (an STD and a BCP)
+    AlsoKnownAs(#[serde(deserialize_with = "one_or_many")] HashSet<C::Holder<DocIdentifier>>),
+    // rfcs of a subseries (bcp, std, fyi)
+    Contains(HashSet<C::Holder<DocIdentifier>>),
     Replaces(C::Holder<DocIdentifier>),
     ReplacedBy(C::Holder<DocIdentifier>),
-    Was(DocIdentifier),
+    Was(C::Holder<DocIdentifier>),
+    NormativeReferences(HashSet<C::Holder<DocIdentifier>>),
+    InformativeReferences(HashSet<C::Holder<DocIdentifier>>),
+    NormativelyReferencedBy(HashSet<C::Holder<DocIdentifier>>),
+    InformativelyReferencedBy(HashSet<C::Holder<DocIdentifier>>),
 }
 
 impl<C> Meta<C>
@@ -38,11 +64,16 @@ impl<C> Meta<C>
             Meta::Updates(list)
             | Meta::Obsoletes(list)
             | Meta::UpdatedBy(list)
-            | Meta::ObsoletedBy(list) => list.len(),
+            | Meta::ObsoletedBy(list)
+            | Meta::AlsoKnownAs(list)
+            | Meta::Contains(list)
+            | Meta::NormativeReferences(list)
+            | Meta::InformativeReferences(list)
+            | Meta::NormativelyReferencedBy(list)
+            | Meta::InformativelyReferencedBy(list) => list.len(),
             Meta::Was(_)
             | Meta::Replaces(_)
-            | Meta::ReplacedBy(_)
-            | Meta::AlsoKnownAs(_) => 1,
+            | Meta::ReplacedBy(_) => 1,
         }
     }
 
@@ -51,9 +82,17 @@ impl<C> Meta<C>
             .into_iter()
             .skip(1)
             .step_by(2)
-            .map(|x| C::Holder::from(name_to_id(x)))
+            .filter_map(|x| name_to_id(x).ok())
+            .map(C::Holder::from)
             .collect()
     }
+    fn inner_text_item(tyype: &str, inner_text: &[&str], index: usize) -> Result<DocIdentifier> {
+        match inner_text.get(index) {
+            Some(item) => name_to_id(item.trim()),
+            None => UnknownMeta(format!("Malformed Meta {tyype} {{{:#?}}}", inner_text)).into(),
+        }
+    }
+
     pub fn from_html(tyype: String, inner_text: Vec<&str>) -> Result<Meta<C>> {
         match tyype.as_str() {
             "updated_by" => {
@@ -73,35 +112,73 @@ impl<C> Meta<C>
                 Ok(obsoleters)
             }
             "was" => {
-                let was = Meta::Was(name_to_id(inner_text[1].trim().to_string()));
+                let was = Meta::Was(C::Holder::from(Self::inner_text_item(&tyype, &inner_text, 1)?));
                 Ok(was)
             }
             "replaces" => {
-                let replaced = Meta::Replaces(C::Holder::from(name_to_id(
-                    inner_text[0].trim().to_string(),
-                )));
+                let replaced = Meta::Replaces(C::Holder::from(Self::inner_text_item(
+                    &tyype,
+                    &inner_text,
+                    0,
+                )?));
                 Ok(replaced)
             }
             "replaced_by" => {
-                let replacer = Meta::ReplacedBy(C::Holder::from(name_to_id(
-                    inner_text[0].trim().to_string(),
-                )));
+                let replacer = Meta::ReplacedBy(C::Holder::from(Self::inner_text_item(
+                    &tyype,
+                    &inner_text,
+                    0,
+                )?));
                 Ok(replacer)
             }
             "also_known_as" => {
-                let known_as = Meta::AlsoKnownAs(name_to_id(inner_text[1].trim().to_string()));
+                let known_as = Meta::AlsoKnownAs(Self::from_inner_text(inner_text));
                 Ok(known_as)
             }
             _ => UnknownMeta(format!("Unknown Meta {tyype} {{{:#?}}}", inner_text)).into(),
         }
     }
 
+    /* build metas from the (document id, reference type) rows of a datatracker references page
+     * references which are not normative (informative, unclassified, possible) are informative */
+    pub fn from_references_html(rows: Vec<(DocIdentifier, String)>, referenced_by: bool) -> Vec<Meta<C>> {
+        let (normative, informative): (Vec<_>, Vec<_>) = rows
+            .into_iter()
+            .partition(|(_, tyype)| tyype.contains("normative"));
+
+        let to_set = |rows: Vec<(DocIdentifier, String)>| -> HashSet<C::Holder<DocIdentifier>> {
+            rows.into_iter().map(|(id, _)| C::Holder::from(id)).collect()
+        };
+        let (normative, informative) = (to_set(normative), to_set(informative));
+
+        let mut metas = Vec::new();
+        if referenced_by {
+            if !normative.is_empty() {
+                metas.push(Meta::NormativelyReferencedBy(normative));
+            }
+            if !informative.is_empty() {
+                metas.push(Meta::InformativelyReferencedBy(informative));
+            }
+        } else {
+            if !normative.is_empty() {
+                metas.push(Meta::NormativeReferences(normative));
+            }
+            if !informative.is_empty() {
+                metas.push(Meta::InformativeReferences(informative));
+            }
+        }
+
+        metas
+    }
+
     fn from_xml_values(from: &Attribute) -> HashSet<C::Holder<DocIdentifier>> {
-        String::from_utf8(from.value.to_ascii_lowercase())
-            .unwrap()
+        String::from_utf8_lossy(&from.value.to_ascii_lowercase())
             .split(',')
+            .map(str::trim)
+            .filter(|x| !x.is_empty())
             // from_xml_value only called for drafts which can only reference rfcs
-            .map(|x| C::Holder::from(format!("rfc{x}")))
+            .filter_map(|x| DocIdentifier::from_number("rfc", x))
+            .map(C::Holder::from)
             .collect()
     }
 
@@ -109,7 +186,10 @@ impl<C> Meta<C>
         match attr.key {
             b"updates" => Ok(Meta::Updates(Self::from_xml_values(attr))),
             b"obsoletes" => Ok(Meta::Obsoletes(Self::from_xml_values(attr))),
-            b"replaces" => Ok(Meta::Replaces(Self::from_xml_values(attr).into_iter().next().unwrap())),
+            b"replaces" => match Self::from_xml_values(attr).into_iter().next() {
+                Some(replaced) => Ok(Meta::Replaces(replaced)),
+                None => UnknownMeta("Malformed Meta replaces {}".to_string()).into(),
+            },
             _ => UnknownMeta(format!(
                 "Unknown Meta {:?} {{{:#?}}}",
                 String::from_utf8(attr.key.to_ascii_lowercase()),
@@ -0,0 +1,170 @@
+use crate::error::Result;
+use crate::source::{block_on, default_source, Conditional, DocSource, SourceFuture, SourceResponse, Validators};
+use crate::{DocIdentifier, IdContainer, IetfDoc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+use std::sync::{Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+use url::Url;
+
+/* When a document was fetched and the validators of the pages it was built from (datatracker page, xml,
+ * references, api queries), to query it again only when one of them changed, see IetfDoc::refresh */
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FetchState {
+    // unix time (seconds) of the last query of the document, None if it was never queried (index, text)
+    pub fetched_at: Option<u64>,
+    // by url
+    pub validators: BTreeMap<String, Validators>,
+}
+
+fn now() -> Option<u64> {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|now| now.as_secs())
+}
+
+/* Result of IetfDoc::refresh */
+#[derive(Debug, Clone)]
+pub enum Refresh<C: IdContainer> {
+    // none of the pages of the document changed, only the fetch time is updated
+    Unchanged(FetchState),
+    Changed(Box<IetfDoc<C>>, FetchState),
+}
+
+/* Records the validators of the documents fetched through the inner source
+ * responses already fetched (by the conditional queries of a refresh) are served without querying them again */
+#[derive(Debug)]
+struct TrackingSource<'a> {
+    inner: &'a dyn DocSource,
+    validators: Mutex<BTreeMap<String, Validators>>,
+    prefetched: Mutex<HashMap<Url, SourceResponse>>,
+}
+
+impl<'a> TrackingSource<'a> {
+    fn new(inner: &'a dyn DocSource) -> Self {
+        Self {
+            inner,
+            validators: Mutex::default(),
+            prefetched: Mutex::default(),
+        }
+    }
+
+    fn prefetch(&self, url: Url, resp: SourceResponse) {
+        self.prefetched.lock().unwrap_or_else(PoisonError::into_inner).insert(url, resp);
+    }
+
+    fn record(&self, url: &Url, resp: &SourceResponse) {
+        self.validators
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(url.to_string(), resp.validators().clone());
+    }
+
+    fn into_state(self) -> FetchState {
+        FetchState {
+            fetched_at: now(),
+            validators: self.validators.into_inner().unwrap_or_else(PoisonError::into_inner),
+        }
+    }
+}
+
+impl DocSource for TrackingSource<'_> {
+    fn get<'b>(&'b self, url: &'b Url) -> SourceFuture<'b> {
+        Box::pin(async move {
+            let prefetched = self.prefetched.lock().unwrap_or_else(PoisonError::into_inner).remove(url);
+            let resp = match prefetched {
+                Some(resp) => resp,
+                None => self.inner.get(url).await?,
+            };
+            self.record(url, &resp);
+
+            Ok(resp)
+        })
+    }
+}
+
+impl<C> IetfDoc<C>
+    where
+        C: IdContainer,
+{
+    /* query a document from its name (like from_name) along with the validators of its pages */
+    pub fn fetch(name: impl Into<String>) -> Result<(IetfDoc<C>, FetchState)> {
+        let name = name.into();
+        block_on(Self::fetch_async(name))
+    }
+
+    pub fn fetch_with(source: &dyn DocSource, name: impl Into<String>) -> Result<(IetfDoc<C>, FetchState)> {
+        let name = name.into();
+        block_on(Self::fetch_with_async(source, name))
+    }
+
+    pub async fn fetch_async(name: impl Into<String>) -> Result<(IetfDoc<C>, FetchState)> {
+        let source = default_source();
+        Self::fetch_with_async(source.as_ref(), name).await
+    }
+
+    pub async fn fetch_with_async(source: &dyn DocSource, name: impl Into<String>) -> Result<(IetfDoc<C>, FetchState)> {
+        let tracking = TrackingSource::new(source);
+        let doc = Self::from_name_with_async(&tracking, name).await?;
+
+        Ok((doc, tracking.into_state()))
+    }
+
+    /* query a document again if one of the pages it was built from changed since they were fetched
+     * pages are queried conditionally (If-None-Match / If-Modified-Since), documents without validators
+     * (never fetched, or from a server which doesn't send them) are always queried again */
+    pub fn refresh(id: &DocIdentifier, state: &FetchState) -> Result<Refresh<C>> {
+        block_on(Self::refresh_async(id, state))
+    }
+
+    pub fn refresh_with(source: &dyn DocSource, id: &DocIdentifier, state: &FetchState) -> Result<Refresh<C>> {
+        block_on(Self::refresh_with_async(source, id, state))
+    }
+
+    pub async fn refresh_async(id: &DocIdentifier, state: &FetchState) -> Result<Refresh<C>> {
+        let source = default_source();
+        Self::refresh_with_async(source.as_ref(), id, state).await
+    }
+
+    pub async fn refresh_with_async(
+        source: &dyn DocSource,
+        id: &DocIdentifier,
+        state: &FetchState,
+    ) -> Result<Refresh<C>> {
+        let tracking = TrackingSource::new(source);
+
+        let mut changed = state.validators.is_empty();
+        for (url, validators) in &state.validators {
+            let Ok(url) = Url::from_str(url) else {
+                changed = true;
+                break;
+            };
+            match source.get_if_modified(&url, validators).await {
+                Ok(Conditional::NotModified) => {}
+                // the other pages are queried again along with the document
+                Ok(Conditional::Modified(resp)) => {
+                    tracking.prefetch(url, resp);
+                    changed = true;
+                    break;
+                }
+                // the error is returned by the query of the document if it still fails
+                Err(_) => {
+                    changed = true;
+                    break;
+                }
+            }
+        }
+
+        if !changed {
+            return Ok(Refresh::Unchanged(FetchState {
+                fetched_at: now(),
+                validators: state.validators.clone(),
+            }));
+        }
+
+        let doc = Self::from_name_with_async(&tracking, id.to_string()).await?;
+        Ok(Refresh::Changed(Box::new(doc), tracking.into_state()))
+    }
+}
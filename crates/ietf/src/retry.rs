@@ -0,0 +1,332 @@
+use crate::error::{DocError, Result};
+use crate::source::{ConditionalFuture, DocSource, SourceFuture, Validators};
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use url::Url;
+
+/* When and how often failed queries are attempted again, only retryable errors are retried */
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    // delay before the first retry, doubled at each retry
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    // give up instead of waiting when the server asks to retry later than this
+    pub max_retry_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 4,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            max_retry_after: Duration::from_secs(120),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /* never retry */
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    /* exponential backoff before the retry number 'attempt' (starting at 0) */
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff)
+    }
+}
+
+/* Called before each retry with the url, the delay before the query is attempted again and the error of the failed one */
+pub type RetryCallback = Arc<dyn Fn(&Url, Duration, &DocError) + Send + Sync>;
+
+/* Retries the queries of the inner source failing with a retryable error (timeouts, 429, 5xx...)
+ * waits for the Retry-After delay of the server when given, exponential backoff otherwise */
+#[derive(Clone)]
+pub struct RetrySource<S: DocSource> {
+    inner: S,
+    policy: RetryPolicy,
+    on_retry: Option<RetryCallback>,
+}
+
+impl<S: DocSource> Debug for RetrySource<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetrySource")
+            .field("inner", &self.inner)
+            .field("policy", &self.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S: DocSource> RetrySource<S> {
+    pub fn new(inner: S, policy: RetryPolicy) -> Self {
+        Self {
+            inner,
+            policy,
+            on_retry: None,
+        }
+    }
+
+    /* be notified of the retries (eg: to report them to the user), retries are silent otherwise */
+    pub fn on_retry(mut self, callback: impl Fn(&Url, Duration, &DocError) + Send + Sync + 'static) -> Self {
+        self.on_retry = Some(Arc::new(callback));
+        self
+    }
+
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    /* run a query of the inner source again until it succeeds, fails with an error which isn't retryable
+     * or runs out of retries */
+    async fn retry<T, F>(&self, url: &Url, query: impl Fn() -> F) -> Result<T>
+        where
+            F: Future<Output = Result<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            let err = match query().await {
+                Err(err) if err.is_retryable() && attempt < self.policy.max_retries => err,
+                result => return result,
+            };
+
+            let delay = match err.retry_after() {
+                Some(delay) if delay > self.policy.max_retry_after => return Err(err),
+                Some(delay) => {
+                    // the server is likely to reject the other queries as well
+                    delay_all_queries(delay);
+                    delay
+                }
+                None => self.policy.backoff(attempt),
+            };
+
+            if let Some(on_retry) = &self.on_retry {
+                on_retry(url, delay, &err);
+            }
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+impl<S: DocSource> DocSource for RetrySource<S> {
+    fn get<'a>(&'a self, url: &'a Url) -> SourceFuture<'a> {
+        Box::pin(self.retry(url, move || self.inner.get(url)))
+    }
+
+    fn get_if_modified<'a>(&'a self, url: &'a Url, validators: &'a Validators) -> ConditionalFuture<'a> {
+        Box::pin(self.retry(url, move || self.inner.get_if_modified(url, validators)))
+    }
+}
+
+/* Spaces out the queries of every HttpSource of the process */
+struct RateLimiter {
+    interval: Option<Duration>,
+    next: Option<Instant>,
+}
+
+// 10 requests per second by default
+static RATE_LIMITER: Mutex<RateLimiter> = Mutex::new(RateLimiter {
+    interval: Some(Duration::from_millis(100)),
+    next: None,
+});
+
+/* limit the number of HTTP queries per second of the whole process, None (or 0) disables the limit */
+pub fn set_rate_limit(requests_per_second: Option<f64>) {
+    let interval = requests_per_second
+        .filter(|rate| rate.is_finite() && *rate > 0.0)
+        .map(|rate| Duration::from_secs_f64(1.0 / rate));
+
+    RATE_LIMITER
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .interval = interval;
+}
+
+/* no query is started before the delay is elapsed */
+fn delay_all_queries(delay: Duration) {
+    let mut limiter = RATE_LIMITER.lock().unwrap_or_else(PoisonError::into_inner);
+    let until = Instant::now() + delay;
+    limiter.next = Some(limiter.next.map_or(until, |next| next.max(until)));
+}
+
+/* wait for the turn of the next query */
+pub(crate) async fn throttle() {
+    let wait = {
+        let mut limiter = RATE_LIMITER.lock().unwrap_or_else(PoisonError::into_inner);
+        let now = Instant::now();
+        let slot = limiter.next.map_or(now, |next| next.max(now));
+        limiter.next = match limiter.interval {
+            Some(interval) => Some(slot + interval),
+            // keep delays set by delay_all_queries
+            None => limiter.next.filter(|next| *next > now),
+        };
+
+        slot - now
+    };
+
+    if !wait.is_zero() {
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/* days since 1970-01-01 of a date of the proleptic gregorian calendar */
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146097 + day_of_era - 719468
+}
+
+/* Retry-After header value: delay in seconds or http date ("Sun, 06 Nov 1994 08:49:37 GMT") */
+pub(crate) fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let parts: Vec<&str> = value.split_whitespace().collect();
+    let [_, day, month, year, time, "GMT"] = parts[..] else {
+        return None;
+    };
+    let month = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"]
+        .iter()
+        .position(|name| *name == month)? as i64
+        + 1;
+    let time: Vec<i64> = time.split(':').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    let [hours, minutes, seconds] = time[..] else {
+        return None;
+    };
+
+    let days = days_from_civil(year.parse().ok()?, month, day.parse().ok()?);
+    let timestamp = days * 86400 + hours * 3600 + minutes * 60 + seconds;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs() as i64;
+
+    Some(Duration::from_secs(timestamp.saturating_sub(now).max(0) as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::{block_on, SourceResponse};
+    use std::str::FromStr;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /* fails with a 503 the first 'failures' queries */
+    #[derive(Debug)]
+    struct Unavailable {
+        failures: u32,
+        queries: AtomicU32,
+    }
+
+    impl DocSource for Unavailable {
+        fn get<'a>(&'a self, url: &'a Url) -> SourceFuture<'a> {
+            Box::pin(async move {
+                if self.queries.fetch_add(1, Ordering::SeqCst) < self.failures {
+                    return Err(DocError::Status {
+                        url: url.to_string(),
+                        status: 503,
+                        retry_after: Some(Duration::ZERO),
+                        source: None,
+                    });
+                }
+                Ok(SourceResponse::new(url.clone(), Vec::new()))
+            })
+        }
+    }
+
+    fn now() -> i64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64
+    }
+
+    fn retried(failures: u32, max_retries: u32) -> (Result<SourceResponse>, u32) {
+        let retries = Arc::new(AtomicU32::new(0));
+        let counter = retries.clone();
+        let source = RetrySource::new(
+            Unavailable {
+                failures,
+                queries: AtomicU32::new(0),
+            },
+            RetryPolicy {
+                max_retries,
+                ..RetryPolicy::default()
+            },
+        )
+        .on_retry(move |_, delay, err| {
+            assert_eq!(delay, Duration::ZERO);
+            assert!(err.is_retryable());
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let url = Url::from_str("https://datatracker.ietf.org/doc/rfc9293/").unwrap();
+        let result = block_on(source.get(&url));
+        (result, retries.load(Ordering::SeqCst))
+    }
+
+    #[test]
+    fn retries_are_notified() {
+        let (result, retries) = retried(2, 4);
+        assert!(result.is_ok());
+        assert_eq!(retries, 2);
+
+        let (result, retries) = retried(3, 1);
+        assert!(result.is_err());
+        assert_eq!(retries, 1);
+    }
+
+    #[test]
+    fn retry_after_seconds() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after(" 0 "), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn retry_after_http_date() {
+        let value = "Fri, 31 Dec 9999 23:59:59 GMT";
+        let expected = days_from_civil(9999, 12, 31) * 86400 + 86399 - now();
+
+        let delay = parse_retry_after(value).unwrap().as_secs() as i64;
+        assert!((delay - expected).abs() <= 2, "{} != {}", delay, expected);
+    }
+
+    #[test]
+    fn retry_after_past_date() {
+        assert_eq!(parse_retry_after("Sun, 06 Nov 1994 08:49:37 GMT"), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn retry_after_bad_input() {
+        for value in [
+            "",
+            "soon",
+            "-5",
+            "1.5",
+            // only IMF-fixdate is supported
+            "Sunday, 06-Nov-94 08:49:37 GMT",
+            "Sun Nov  6 08:49:37 1994",
+            "Sun, 06 Nov 1994 08:49:37 UTC",
+            "Sun, 06 Foo 1994 08:49:37 GMT",
+            "Sun, 06 Nov 1994 08:49 GMT",
+        ] {
+            assert_eq!(parse_retry_after(value), None, "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn civil_days() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11017);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+    }
+}
@@ -0,0 +1,385 @@
+use crate::error::{DocError, DocError::*, Result};
+use crate::source::{block_on, default_source, DocSource};
+use crate::url::{url_config, SourceUrl};
+use crate::{DocIdentifier, IdContainer, IetfDoc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use similar::{ChangeTag, TextDiff};
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+use url::Url;
+
+/* A revision of a draft ("03") along with its publication date (YYYY-MM-DD) when known */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Revision {
+    pub revision: String,
+    pub date: Option<String>,
+}
+
+/* whether a line (or a word) is in both texts, only in the old one or only in the new one */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffTag {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/* A line of a diff along with its line numbers (from 1) in the old and new texts
+ * words are the parts of a modified line, changed or not, empty for other lines */
+#[derive(Debug, Clone)]
+pub struct DiffLine {
+    pub tag: DiffTag,
+    pub old_line: Option<usize>,
+    pub new_line: Option<usize>,
+    pub text: String,
+    pub words: Vec<(DiffTag, String)>,
+}
+
+/* Line diff of two texts, the modified lines are also diffed word by word */
+#[derive(Debug, Clone, Default)]
+pub struct DocDiff {
+    pub lines: Vec<DiffLine>,
+}
+
+impl DocDiff {
+    pub fn new(old: &str, new: &str) -> DocDiff {
+        let diff = TextDiff::from_lines(old, new);
+        let trim = |text: &str| text.trim_end_matches(['\r', '\n']).to_string();
+
+        let mut lines = Vec::new();
+        for op in diff.ops() {
+            for change in diff.iter_inline_changes(op) {
+                let tag = match change.tag() {
+                    ChangeTag::Equal => DiffTag::Equal,
+                    ChangeTag::Delete => DiffTag::Delete,
+                    ChangeTag::Insert => DiffTag::Insert,
+                };
+
+                // only the lines replaced by similar ones have emphasized words
+                let words = if change.values().iter().any(|(emphasized, _)| *emphasized) {
+                    change
+                        .iter_strings_lossy()
+                        .map(|(emphasized, word)| (if emphasized { tag } else { DiffTag::Equal }, trim(&word)))
+                        .filter(|(_, word)| !word.is_empty())
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+
+                lines.push(DiffLine {
+                    tag,
+                    old_line: change.old_index().map(|index| index + 1),
+                    new_line: change.new_index().map(|index| index + 1),
+                    text: trim(&change.iter_strings_lossy().map(|(_, word)| word).collect::<String>()),
+                    words,
+                });
+            }
+        }
+
+        DocDiff { lines }
+    }
+
+    fn count(&self, tag: DiffTag) -> usize {
+        self.lines.iter().filter(|line| line.tag == tag).count()
+    }
+
+    pub fn inserted(&self) -> usize {
+        self.count(DiffTag::Insert)
+    }
+
+    pub fn deleted(&self) -> usize {
+        self.count(DiffTag::Delete)
+    }
+
+    pub fn is_unchanged(&self) -> bool {
+        self.lines.iter().all(|line| line.tag == DiffTag::Equal)
+    }
+
+    /* unified diff of the changed lines and of 'context' unchanged lines around them */
+    pub fn unified(&self, context: usize) -> String {
+        let mut shown = vec![false; self.lines.len()];
+        for (idx, line) in self.lines.iter().enumerate() {
+            if line.tag != DiffTag::Equal {
+                let end = (idx + context).min(self.lines.len() - 1);
+                shown[idx.saturating_sub(context)..=end].fill(true);
+            }
+        }
+
+        let mut unified = String::new();
+        // next line numbers in the old and new texts
+        let (mut old, mut new) = (1, 1);
+        let mut last_shown = None;
+        for (idx, line) in self.lines.iter().enumerate() {
+            if shown[idx] {
+                if last_shown.is_none_or(|last| last + 1 != idx) {
+                    unified.push_str(&format!("@@ -{} +{} @@\n", old, new));
+                }
+                let prefix = match line.tag {
+                    DiffTag::Equal => ' ',
+                    DiffTag::Delete => '-',
+                    DiffTag::Insert => '+',
+                };
+                unified.push_str(&format!("{}{}\n", prefix, line.text));
+                last_shown = Some(idx);
+            }
+
+            match line.tag {
+                DiffTag::Equal => (old, new) = (old + 1, new + 1),
+                DiffTag::Delete => old += 1,
+                DiffTag::Insert => new += 1,
+            }
+        }
+
+        unified
+    }
+}
+
+impl Display for DocDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.unified(3))
+    }
+}
+
+impl<C> IetfDoc<C>
+    where
+        C: IdContainer,
+{
+    /* every revision of a draft with its date from the datatracker api, oldest first
+     * empty for other documents */
+    pub fn revision_history(id: &DocIdentifier) -> Result<Vec<Revision>> {
+        block_on(Self::revision_history_async(id))
+    }
+
+    pub fn revision_history_with(source: &dyn DocSource, id: &DocIdentifier) -> Result<Vec<Revision>> {
+        block_on(Self::revision_history_with_async(source, id))
+    }
+
+    pub async fn revision_history_async(id: &DocIdentifier) -> Result<Vec<Revision>> {
+        let source = default_source();
+        Self::revision_history_with_async(source.as_ref(), id).await
+    }
+
+    pub async fn revision_history_with_async(source: &dyn DocSource, id: &DocIdentifier) -> Result<Vec<Revision>> {
+        let DocIdentifier::Draft { name, .. } = id else {
+            return Ok(Vec::new());
+        };
+
+        let mut url = url_config().datatracker.join("api/v1/doc/newrevisiondocevent/")?;
+        url.query_pairs_mut()
+            .append_pair("doc__name", name)
+            .append_pair("limit", "100")
+            .append_pair("format", "json");
+
+        // by revision number
+        let mut revisions: Vec<(u32, Revision)> = Vec::new();
+        let mut next = Some(url);
+        while let Some(url) = next.take() {
+            let json = source.get(&url).await?.json::<Value>()?;
+            next = match json.pointer("/meta/next") {
+                Some(Value::String(next)) => Some(url.join(next)?),
+                _ => None,
+            };
+
+            let objects = json
+                .get("objects")
+                .and_then(Value::as_array)
+                .ok_or_else(|| DocError::json(&url, "no 'objects' list"))?;
+            for obj in objects {
+                let revision = obj
+                    .get("rev")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| DocError::json(&url, format!("missing 'rev' in {}", obj)))?;
+                // revisions are numbers ("03"), others can neither be ordered nor downloaded
+                let Ok(number) = revision.parse::<u32>() else {
+                    continue;
+                };
+                revisions.push((number, Revision {
+                    revision: revision.to_string(),
+                    // "2023-07-04T12:00:00"
+                    date: obj.get("time").and_then(Value::as_str).and_then(|time| time.get(..10)).map(str::to_string),
+                }));
+            }
+        }
+
+        revisions.sort_by_key(|(number, _)| *number);
+        revisions.dedup_by_key(|(number, _)| *number);
+
+        Ok(revisions.into_iter().map(|(_, revision)| revision).collect())
+    }
+
+    /* url of the text of a revision ("03") of this draft, in the archive of download_raw */
+    fn revision_url(&self, revision: &str) -> Result<Url> {
+        let id = self.summary.id.with_revision(revision).ok_or_else(|| {
+            Identifier(format!("no revision '{}' of {}", revision, self.summary.id))
+        })?;
+
+        SourceUrl::new(&id)?.raw(false)
+    }
+
+    pub fn download_revision(&self, revision: &str) -> Result<String> {
+        block_on(self.download_revision_async(revision))
+    }
+
+    pub fn download_revision_with(&self, source: &dyn DocSource, revision: &str) -> Result<String> {
+        block_on(self.download_revision_with_async(source, revision))
+    }
+
+    // like download_raw_async, the futures don't borrow the document
+    pub fn download_revision_async(&self, revision: &str) -> impl Future<Output = Result<String>> + Send {
+        let url = self.revision_url(revision);
+        async move {
+            let source = default_source();
+            Self::download_url(source.as_ref(), url?).await
+        }
+    }
+
+    pub fn download_revision_with_async<'a>(
+        &self,
+        source: &'a dyn DocSource,
+        revision: &str,
+    ) -> impl Future<Output = Result<String>> + Send + 'a {
+        let url = self.revision_url(revision);
+        async move { Self::download_url(source, url?).await }
+    }
+
+    /* diff of the texts of two revisions of this draft */
+    pub fn diff_revisions(&self, old: &str, new: &str) -> Result<DocDiff> {
+        block_on(self.diff_revisions_async(old, new))
+    }
+
+    pub fn diff_revisions_with(&self, source: &dyn DocSource, old: &str, new: &str) -> Result<DocDiff> {
+        block_on(self.diff_revisions_with_async(source, old, new))
+    }
+
+    pub fn diff_revisions_async(&self, old: &str, new: &str) -> impl Future<Output = Result<DocDiff>> + Send {
+        let urls = (self.revision_url(old), self.revision_url(new));
+        async move {
+            let source = default_source();
+            Self::diff_urls(source.as_ref(), urls.0?, urls.1?).await
+        }
+    }
+
+    pub fn diff_revisions_with_async<'a>(
+        &self,
+        source: &'a dyn DocSource,
+        old: &str,
+        new: &str,
+    ) -> impl Future<Output = Result<DocDiff>> + Send + 'a {
+        let urls = (self.revision_url(old), self.revision_url(new));
+        async move { Self::diff_urls(source, urls.0?, urls.1?).await }
+    }
+
+    async fn diff_urls(source: &dyn DocSource, old: Url, new: Url) -> Result<DocDiff> {
+        let (old, new) = tokio::try_join!(Self::download_url(source, old), Self::download_url(source, new))?;
+
+        Ok(DocDiff::new(&old, &new))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(diff: &DocDiff) -> Vec<(DiffTag, Option<usize>, Option<usize>, &str)> {
+        diff.lines
+            .iter()
+            .map(|line| (line.tag, line.old_line, line.new_line, line.text.as_str()))
+            .collect()
+    }
+
+    /* "1\n2\n...\n10\n" with some lines replaced */
+    fn numbered(replaced: &[(usize, &str)]) -> String {
+        (1..=10)
+            .map(|line| {
+                let text = replaced.iter().find(|(number, _)| *number == line).map(|(_, text)| text.to_string());
+                format!("{}\n", text.unwrap_or_else(|| line.to_string()))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn inserted_line() {
+        let diff = DocDiff::new("a\nb\n", "a\nx\nb\n");
+
+        assert_eq!(
+            tags(&diff),
+            vec![
+                (DiffTag::Equal, Some(1), Some(1), "a"),
+                (DiffTag::Insert, None, Some(2), "x"),
+                (DiffTag::Equal, Some(2), Some(3), "b"),
+            ]
+        );
+        assert_eq!((diff.inserted(), diff.deleted()), (1, 0));
+        assert!(diff.lines.iter().all(|line| line.words.is_empty()));
+    }
+
+    #[test]
+    fn deleted_line() {
+        let diff = DocDiff::new("a\nb\nc\n", "a\nc\n");
+
+        assert_eq!(
+            tags(&diff),
+            vec![
+                (DiffTag::Equal, Some(1), Some(1), "a"),
+                (DiffTag::Delete, Some(2), None, "b"),
+                (DiffTag::Equal, Some(3), Some(2), "c"),
+            ]
+        );
+        assert_eq!((diff.inserted(), diff.deleted()), (0, 1));
+        assert_eq!(diff.unified(0), "@@ -2 +2 @@\n-b\n");
+    }
+
+    #[test]
+    fn modified_line_words() {
+        let diff = DocDiff::new("the quick brown fox\n", "the slow brown fox\n");
+
+        let [old, new] = &diff.lines[..] else {
+            panic!("expected a deleted and an inserted line, got {:?}", diff.lines);
+        };
+        assert_eq!((old.tag, old.text.as_str()), (DiffTag::Delete, "the quick brown fox"));
+        assert_eq!((new.tag, new.text.as_str()), (DiffTag::Insert, "the slow brown fox"));
+
+        // the changed words only are tagged, the parts make up the line
+        let changed = |line: &DiffLine| -> Vec<String> {
+            line.words.iter().filter(|(tag, _)| *tag != DiffTag::Equal).map(|(_, word)| word.clone()).collect()
+        };
+        assert_eq!(changed(old), vec!["quick"]);
+        assert_eq!(changed(new), vec!["slow"]);
+        assert!(old.words.iter().any(|(tag, word)| *tag == DiffTag::Equal && word.contains("brown")));
+        for line in [old, new] {
+            assert_eq!(line.words.iter().map(|(_, word)| word.as_str()).collect::<String>(), line.text);
+        }
+    }
+
+    #[test]
+    fn hunks() {
+        let diff = DocDiff::new(&numbered(&[]), &numbered(&[(2, "two"), (10, "ten")]));
+
+        // the last line changed, its context stops at the end of the texts
+        assert_eq!(
+            diff.unified(1),
+            "@@ -1 +1 @@\n 1\n-2\n+two\n 3\n@@ -9 +9 @@\n 9\n-10\n+ten\n"
+        );
+        // hunks whose contexts touch are merged
+        let merged = diff.unified(4);
+        assert_eq!(merged.matches("@@ -").count(), 1);
+        assert!(merged.starts_with("@@ -1 +1 @@\n 1\n-2\n+two\n"));
+        assert!(merged.ends_with(" 9\n-10\n+ten\n"));
+
+        // line numbers after an insertion
+        let diff = DocDiff::new(&numbered(&[]), &numbered(&[]).replace("5\n", "5\nfive\n"));
+        assert_eq!(diff.unified(1), "@@ -5 +5 @@\n 5\n+five\n 6\n");
+    }
+
+    #[test]
+    fn identical_texts() {
+        let text = numbered(&[]);
+        let diff = DocDiff::new(&text, &text);
+
+        assert!(diff.is_unchanged());
+        assert_eq!((diff.inserted(), diff.deleted()), (0, 0));
+        assert_eq!(diff.lines.len(), 10);
+        assert_eq!(diff.unified(3), "");
+        assert_eq!(DocDiff::new("", "").unified(3), "");
+    }
+}
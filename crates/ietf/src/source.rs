@@ -0,0 +1,371 @@
+use crate::error::{DocError::*, Result};
+use crate::http::HttpSource;
+use crate::retry::{RetryPolicy, RetrySource};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use std::collections::hash_map::DefaultHasher;
+use std::ffi::OsString;
+use std::fs;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock, PoisonError, RwLock};
+use std::thread;
+use tokio::runtime::{Handle, Runtime};
+use url::Url;
+
+/* Validators of a fetched document (ETag / Last-Modified headers)
+ * sent back by DocSource::get_if_modified to fetch the document only if it changed */
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+/* validators of a local file (fixture, file:// mirror): a hash of its content as ETag */
+pub(crate) fn content_validators(body: &[u8]) -> Validators {
+    let mut hasher = DefaultHasher::new();
+    body.hash(&mut hasher);
+
+    Validators {
+        etag: Some(format!("\"{:016x}\"", hasher.finish())),
+        last_modified: None,
+    }
+}
+
+/* Body of a document fetched from a DocSource
+ * url is the final url of the document (after redirections) */
+#[derive(Debug, Clone)]
+pub struct SourceResponse {
+    url: Url,
+    body: Vec<u8>,
+    validators: Validators,
+}
+
+impl SourceResponse {
+    pub fn new(url: Url, body: Vec<u8>) -> Self {
+        Self {
+            url,
+            body,
+            validators: Validators::default(),
+        }
+    }
+
+    pub fn with_validators(mut self, validators: Validators) -> Self {
+        self.validators = validators;
+        self
+    }
+
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    pub fn validators(&self) -> &Validators {
+        &self.validators
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.body
+    }
+
+    pub fn text(self) -> Result<String> {
+        String::from_utf8(self.body)
+            .map_err(|err| Query(format!("Error decoding {}: {}", self.url, err)))
+    }
+
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T> {
+        serde_json::from_slice(&self.body).map_err(|err| Json {
+            url: self.url.to_string(),
+            desc: "malformed json".to_string(),
+            source: Some(Arc::new(err)),
+        })
+    }
+}
+
+/* Response being fetched by a DocSource, boxed to keep DocSource object safe */
+pub type SourceFuture<'a> = Pin<Box<dyn Future<Output = Result<SourceResponse>> + Send + 'a>>;
+
+/* Result of a conditional query, see DocSource::get_if_modified */
+#[derive(Debug, Clone)]
+pub enum Conditional {
+    Modified(SourceResponse),
+    NotModified,
+}
+
+pub type ConditionalFuture<'a> = Pin<Box<dyn Future<Output = Result<Conditional>> + Send + 'a>>;
+
+/* Transport used to fetch documents (html pages, xml, raw text, api queries) */
+pub trait DocSource: Send + Sync + Debug {
+    // must resolve to an error if the document could not be fetched successfully
+    fn get<'a>(&'a self, url: &'a Url) -> SourceFuture<'a>;
+
+    // fetch a document only if it changed since it was fetched with these validators
+    // sources which can't tell fetch it every time
+    fn get_if_modified<'a>(&'a self, url: &'a Url, validators: &'a Validators) -> ConditionalFuture<'a> {
+        let _ = validators;
+        Box::pin(async move { Ok(Conditional::Modified(self.get(url).await?)) })
+    }
+}
+
+/* Fetch documents from a directory of fixtures, see FixtureSource::path_for for the layout */
+#[derive(Debug, Clone)]
+pub struct FixtureSource {
+    root: PathBuf,
+}
+
+fn sanitize(part: &str) -> String {
+    part.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+// length of the readable part of the queries in fixture file names
+const QUERY_NAME_LENGTH: usize = 96;
+
+/* FNV-1a, stable across builds unlike DefaultHasher so that fixture names don't change */
+fn stable_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+impl FixtureSource {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &PathBuf {
+        &self.root
+    }
+
+    /* maps an url to a file in the fixture directory
+     *   https://www.rfc-editor.org/rfc/rfc9293.txt => {root}/www.rfc-editor.org/rfc/rfc9293.txt
+     *   https://datatracker.ietf.org/doc/rfc9293 => {root}/datatracker.ietf.org/doc/rfc9293/index
+     *   https://host/api/?a=b&c=d => {root}/host/api/index_a_b_c_d_{hash of the query}
+     * paths without a file extension are stored as 'index' files since they may also be directories
+     * queries are named after their (sanitized, truncated) text and told apart by their hash */
+    pub fn path_for(&self, url: &Url) -> PathBuf {
+        let mut path = self.root.join(sanitize(url.host_str().unwrap_or("localhost")));
+        let segments: Vec<&str> = url
+            .path_segments()
+            .map(|segments| segments.filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default();
+
+        let file = match segments.split_last() {
+            Some((last, dirs)) if last.contains('.') => {
+                dirs.iter().for_each(|dir| path.push(sanitize(dir)));
+                sanitize(last)
+            }
+            _ => {
+                segments.iter().for_each(|dir| path.push(sanitize(dir)));
+                "index".to_string()
+            }
+        };
+
+        match url.query() {
+            Some(query) => {
+                let mut name = sanitize(query);
+                name.truncate(QUERY_NAME_LENGTH);
+                path.join(format!("{}_{}_{:016x}", file, name, stable_hash(query.as_bytes())))
+            }
+            None => path.join(file),
+        }
+    }
+
+    /* file holding the final url of a fixture when the query was redirected */
+    fn redirect_path(path: &Path) -> PathBuf {
+        let mut redirect = OsString::from(path.as_os_str());
+        redirect.push(".url");
+        redirect.into()
+    }
+
+    /* store a response so that it is served for its url later on */
+    pub fn store(&self, url: &Url, resp: &SourceResponse) -> Result<()> {
+        let path = self.path_for(url);
+        let write = || -> std::io::Result<()> {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, resp.bytes())?;
+            if resp.url() != url {
+                fs::write(Self::redirect_path(&path), resp.url().as_str())?;
+            }
+            Ok(())
+        };
+
+        write().map_err(|err| {
+            Query(format!(
+                "Error recording {} to {}: {}",
+                url,
+                path.display(),
+                err
+            ))
+        })
+    }
+}
+
+impl FixtureSource {
+    fn read(&self, url: &Url) -> Result<SourceResponse> {
+        let path = self.path_for(url);
+        let body = match fs::read(&path) {
+            Ok(body) => body,
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                let err = io::Error::new(err.kind(), format!("no fixture at {}", path.display()));
+                return NotFound {
+                    url: url.to_string(),
+                    source: Some(Arc::new(err)),
+                }
+                .into()
+            }
+            Err(err) => {
+                return Query(format!(
+                    "Error querying {}: no fixture at {} ({})",
+                    url,
+                    path.display(),
+                    err
+                ))
+                .into()
+            }
+        };
+
+        // replay redirections (eg: unknown documents redirected to the datatracker search)
+        let final_url = match fs::read_to_string(Self::redirect_path(&path)) {
+            Ok(redirect) => Url::from_str(redirect.trim())?,
+            Err(_) => url.clone(),
+        };
+
+        let validators = content_validators(&body);
+        Ok(SourceResponse::new(final_url, body).with_validators(validators))
+    }
+}
+
+impl DocSource for FixtureSource {
+    fn get<'a>(&'a self, url: &'a Url) -> SourceFuture<'a> {
+        Box::pin(async move { self.read(url) })
+    }
+
+    fn get_if_modified<'a>(&'a self, url: &'a Url, validators: &'a Validators) -> ConditionalFuture<'a> {
+        Box::pin(async move {
+            let resp = self.read(url)?;
+            if validators.etag.is_some() && resp.validators().etag == validators.etag {
+                return Ok(Conditional::NotModified);
+            }
+
+            Ok(Conditional::Modified(resp))
+        })
+    }
+}
+
+/* Records every response fetched through the inner source to an archive directory
+ * The archive can be replayed later on using a FixtureSource on the same directory */
+#[derive(Debug, Clone)]
+pub struct RecordingSource<S: DocSource> {
+    inner: S,
+    archive: FixtureSource,
+}
+
+impl<S: DocSource> RecordingSource<S> {
+    pub fn new(inner: S, archive: impl Into<PathBuf>) -> Self {
+        Self {
+            inner,
+            archive: FixtureSource::new(archive),
+        }
+    }
+
+    pub fn archive(&self) -> &PathBuf {
+        self.archive.root()
+    }
+}
+
+impl<S: DocSource> DocSource for RecordingSource<S> {
+    fn get<'a>(&'a self, url: &'a Url) -> SourceFuture<'a> {
+        Box::pin(async move {
+            let resp = self.inner.get(url).await?;
+            self.archive.store(url, &resp)?;
+
+            Ok(resp)
+        })
+    }
+
+    fn get_if_modified<'a>(&'a self, url: &'a Url, validators: &'a Validators) -> ConditionalFuture<'a> {
+        Box::pin(async move {
+            let conditional = self.inner.get_if_modified(url, validators).await?;
+            if let Conditional::Modified(ref resp) = conditional {
+                self.archive.store(url, resp)?;
+            }
+
+            Ok(conditional)
+        })
+    }
+}
+
+/* Replaying a recorded archive is reading it as fixtures */
+pub type ReplaySource = FixtureSource;
+
+/* Source used when none is given explicitly (eg: when resolving ResolvableEntries) */
+static DEFAULT_SOURCE: RwLock<Option<Arc<dyn DocSource>>> = RwLock::new(None);
+
+pub fn set_default_source(source: impl DocSource + 'static) {
+    *DEFAULT_SOURCE.write().unwrap_or_else(PoisonError::into_inner) = Some(Arc::new(source));
+}
+
+/* returns the source set with set_default_source, HttpSource retrying with the default RetryPolicy otherwise
+ * the fallback is created once so that its connections are reused by every query */
+pub fn default_source() -> Arc<dyn DocSource> {
+    static FALLBACK: OnceLock<Arc<dyn DocSource>> = OnceLock::new();
+
+    DEFAULT_SOURCE
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+        .unwrap_or_else(|| {
+            FALLBACK
+                .get_or_init(|| Arc::new(RetrySource::new(HttpSource::default(), RetryPolicy::default())))
+                .clone()
+        })
+}
+
+/* Runtime driving the queries of the blocking API */
+fn runtime() -> Result<&'static Runtime> {
+    static RUNTIME: OnceLock<io::Result<Runtime>> = OnceLock::new();
+    RUNTIME
+        .get_or_init(|| tokio::runtime::Builder::new_multi_thread().enable_all().build())
+        .as_ref()
+        .map_err(|err| Query(format!("Error starting the tokio runtime: {}", err)))
+}
+
+/* run a query from blocking code
+ * when already inside a tokio runtime the query is driven from another thread since blocking there panics */
+pub(crate) fn block_on<T, F>(future: F) -> Result<T>
+    where
+        F: Future<Output = Result<T>> + Send,
+        T: Send,
+{
+    if Handle::try_current().is_err() {
+        return runtime()?.block_on(future);
+    }
+
+    thread::scope(|scope| {
+        scope
+            .spawn(|| runtime()?.block_on(future))
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+}
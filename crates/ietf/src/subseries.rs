@@ -0,0 +1,126 @@
+use crate::error::{DocError, Result};
+use crate::meta::Meta;
+use crate::source::{block_on, default_source, DocSource};
+use crate::url::url_config;
+use crate::{name_to_id, DocIdentifier, IdContainer, IetfDoc, MetaMap, Summary};
+use serde_json::Value;
+use std::collections::HashSet;
+
+/* "BCP 14", title of a subseries without one */
+pub(crate) fn subseries_title(id: &DocIdentifier) -> String {
+    match id {
+        DocIdentifier::Bcp(number) => format!("BCP {}", number),
+        DocIdentifier::Std(number) => format!("STD {}", number),
+        DocIdentifier::Fyi(number) => format!("FYI {}", number),
+        other => other.to_string(),
+    }
+}
+
+/* Rfcs of a subseries (bcp, std, fyi) from the datatracker api, see IetfDoc::subseries_members */
+#[derive(Debug, Default)]
+pub struct SubseriesMembers {
+    pub members: Vec<DocIdentifier>,
+    // targets which aren't documents, skipped
+    pub errors: Vec<DocError>,
+}
+
+impl<C> IetfDoc<C>
+    where
+        C: IdContainer,
+{
+    /* rfcs of a subseries, empty for other documents */
+    pub fn subseries_members(id: &DocIdentifier) -> Result<SubseriesMembers> {
+        block_on(Self::subseries_members_async(id))
+    }
+
+    pub fn subseries_members_with(source: &dyn DocSource, id: &DocIdentifier) -> Result<SubseriesMembers> {
+        block_on(Self::subseries_members_with_async(source, id))
+    }
+
+    pub async fn subseries_members_async(id: &DocIdentifier) -> Result<SubseriesMembers> {
+        let source = default_source();
+        Self::subseries_members_with_async(source.as_ref(), id).await
+    }
+
+    pub async fn subseries_members_with_async(source: &dyn DocSource, id: &DocIdentifier) -> Result<SubseriesMembers> {
+        let mut found = SubseriesMembers::default();
+        if !id.is_subseries() {
+            return Ok(found);
+        }
+
+        let mut url = url_config().datatracker.join("api/v1/doc/relateddocument/")?;
+        url.query_pairs_mut()
+            .append_pair("source__name", &id.to_string())
+            .append_pair("relationship__slug", "contains")
+            .append_pair("limit", "100")
+            .append_pair("format", "json");
+
+        let mut next = Some(url);
+        while let Some(url) = next.take() {
+            let json = source.get(&url).await?.json::<Value>()?;
+            next = match json.pointer("/meta/next") {
+                Some(Value::String(next)) => Some(url.join(next)?),
+                _ => None,
+            };
+
+            let objects = json
+                .get("objects")
+                .and_then(Value::as_array)
+                .ok_or_else(|| DocError::json(&url, "no 'objects' list"))?;
+            for obj in objects {
+                // "/api/v1/doc/document/rfc2119/"
+                let target = obj
+                    .get("target")
+                    .and_then(Value::as_str)
+                    .and_then(|target| target.trim_end_matches('/').rsplit('/').next())
+                    .ok_or_else(|| DocError::json(&url, format!("missing 'target' in {}", obj)))
+                    .and_then(name_to_id);
+                match target {
+                    Ok(member) => found.members.push(member),
+                    Err(err) => found.errors.push(err),
+                }
+            }
+        }
+
+        found.members.sort();
+        found.members.dedup();
+
+        Ok(found)
+    }
+
+    /* a subseries along with the rfcs it contains, its summary is queried unless provided */
+    pub(crate) async fn from_subseries(
+        source: &dyn DocSource,
+        id: &DocIdentifier,
+        provided: Option<Summary>,
+    ) -> Result<IetfDoc<C>> {
+        let summary = match provided {
+            Some(summary) => summary,
+            None => {
+                let mut url = url_config().datatracker.join(&format!("api/v1/doc/document/{}/", id))?;
+                url.set_query(Some("format=json"));
+                let json = source.get(&url).await?.json::<Value>()?;
+
+                let title = match json.get("title") {
+                    Some(Value::String(title)) if !title.trim().is_empty() => title.trim().to_string(),
+                    _ => subseries_title(id),
+                };
+                Summary::new(id.clone(), String::new(), false, title)?
+            }
+        };
+
+        // the subseries is kept without its targets which aren't documents
+        let members = Self::subseries_members_with_async(source, &summary.id).await?.members;
+        let mut meta: MetaMap<C> = MetaMap::default();
+        if !members.is_empty() {
+            meta.push_meta(Meta::Contains(members.into_iter().map(C::Holder::from).collect::<HashSet<_>>()));
+        }
+
+        Ok(IetfDoc {
+            summary,
+            meta,
+            revisions: Vec::new(),
+            errata: None,
+        })
+    }
+}
@@ -0,0 +1,365 @@
+use crate::error::{DocError::*, Result};
+use crate::index::format_date;
+use crate::meta::Meta;
+use crate::doc::{group_from_name, SummaryDetails};
+use crate::{name_to_id, DocIdentifier, IdContainer, IetfDoc, MetaMap, Summary};
+use regex::Regex;
+use std::mem;
+use std::sync::OnceLock;
+
+// header lines are made of a left and a right column, right column items start after this column
+const RIGHT_COLUMN: usize = 36;
+
+/* Fields read from the header block of a plain-text rfc or draft */
+#[derive(Debug, Clone, Default)]
+pub struct TextHeader {
+    pub id: Option<DocIdentifier>,
+    pub is_rfc: bool,
+    pub revision: Option<String>,
+    pub title: Option<String>,
+    // Category (rfcs) or Intended status (drafts)
+    pub status: Option<String>,
+    pub date: Option<String>,
+    pub expires: Option<String>,
+    pub issn: Option<String>,
+    pub authors: Vec<String>,
+    pub updates: Vec<DocIdentifier>,
+    pub obsoletes: Vec<DocIdentifier>,
+    // STD / BCP / FYI numbers
+    pub subseries: Vec<DocIdentifier>,
+    // IETF, IRTF, IAB or ISE, from the first line of rfcs
+    pub stream: Option<String>,
+    // number of form feed separated pages, None when the text isn't paginated
+    pub pages: Option<u32>,
+    pub abstract_text: Option<String>,
+}
+
+/* relation whose list may continue on the next lines of the left column */
+#[derive(Debug, Clone, Copy)]
+enum TextRelation {
+    Updates,
+    Obsoletes,
+}
+
+/* date in "August 2022", "4 July 2023" or "July 4, 2023" format */
+fn parse_date(text: &str) -> Option<String> {
+    static DATE: OnceLock<Regex> = OnceLock::new();
+    let captures = DATE
+        .get_or_init(|| {
+            Regex::new(
+                r"(?i)^(?:(\d{1,2})\s+)?(January|February|March|April|May|June|July|August|September|October|November|December)\s+(?:(\d{1,2}),\s+)?(\d{4})$",
+            )
+                .unwrap()
+        })
+        .captures(text.trim())?;
+
+    let day = captures.get(1).or_else(|| captures.get(3)).map(|day| day.as_str());
+    Some(format_date(&captures[4], Some(&captures[2]), day))
+}
+
+/* initials followed by a name: "W. Eddy, Ed.", "J-P. Vasseur", "D. Eastlake 3rd" */
+fn is_author(text: &str) -> bool {
+    static AUTHOR: OnceLock<Regex> = OnceLock::new();
+    AUTHOR
+        .get_or_init(|| {
+            Regex::new(r"^(?:[A-Z][a-z]?(?:-[A-Z][a-z]?)?\.\s?)+[A-Z][\w'-]+(?: [\w'-]+)*(?:, Ed\.)?$").unwrap()
+        })
+        .is_match(text)
+}
+
+/* draft name line below the title: "draft-ietf-tcpm-rfc793bis-28", "<draft-foo-bar-01.txt>" */
+fn draft_name(line: &str) -> Option<(String, String)> {
+    static DRAFT_NAME: OnceLock<Regex> = OnceLock::new();
+    let captures = DRAFT_NAME
+        .get_or_init(|| Regex::new(r"^<?(draft-[a-z0-9-]+?-(\d{2}))(?:\.txt)?>?$").unwrap())
+        .captures(line.trim())?;
+
+    Some((captures[1].to_string(), captures[2].to_string()))
+}
+
+/* stream line of rfcs: "Internet Engineering Task Force (IETF)", "Independent Submission" */
+fn stream_name(text: &str) -> Option<String> {
+    static STREAM: OnceLock<Regex> = OnceLock::new();
+    if text.eq_ignore_ascii_case("independent submission") {
+        return Some("ISE".to_string());
+    }
+    let captures = STREAM
+        .get_or_init(|| Regex::new(r"^Internet .+ \((IETF|IRTF|IAB)\)$").unwrap())
+        .captures(text)?;
+
+    Some(captures[1].to_string())
+}
+
+/* numbers of a header value */
+fn numbers() -> &'static Regex {
+    static NUMBER: OnceLock<Regex> = OnceLock::new();
+    NUMBER.get_or_init(|| Regex::new(r"\d+").unwrap())
+}
+
+/* rfc numbers of an "Updates:" / "Obsoletes:" value, "(if approved)" is ignored */
+fn rfc_list(value: &str) -> Vec<DocIdentifier> {
+    numbers()
+        .find_iter(value)
+        .filter_map(|number| DocIdentifier::from_number("rfc", number.as_str()))
+        .collect()
+}
+
+/* (column, text) of the items of a line, items are separated by at least two spaces */
+fn columns(line: &str) -> Vec<(usize, &str)> {
+    static ITEM: OnceLock<Regex> = OnceLock::new();
+    ITEM.get_or_init(|| Regex::new(r"\S+(?: \S+)*").unwrap())
+        .find_iter(line)
+        .map(|item| (item.start(), item.as_str()))
+        .collect()
+}
+
+impl TextHeader {
+    fn push_relation(&mut self, relation: TextRelation, ids: Vec<DocIdentifier>) {
+        match relation {
+            TextRelation::Updates => self.updates.extend(ids),
+            TextRelation::Obsoletes => self.obsoletes.extend(ids),
+        }
+    }
+
+    /* summary fields of the header, the group is guessed from the draft name */
+    pub(crate) fn details(&self) -> SummaryDetails {
+        SummaryDetails {
+            status: self.status.clone(),
+            date: self.date.clone(),
+            authors: self.authors.clone(),
+            expires: self.expires.clone(),
+            stream: self.stream.clone(),
+            group: self.id.as_ref().and_then(group_from_name),
+            pages: self.pages,
+            abstract_text: self.abstract_text.clone(),
+        }
+    }
+
+    /* Updates / Obsoletes relations and the subseries as AlsoKnownAs */
+    pub fn metas<C: IdContainer>(&self) -> Vec<Meta<C>> {
+        let to_set = |ids: &Vec<DocIdentifier>| ids.iter().cloned().map(C::Holder::from).collect();
+
+        let mut metas = Vec::new();
+        if !self.updates.is_empty() {
+            metas.push(Meta::Updates(to_set(&self.updates)));
+        }
+        if !self.obsoletes.is_empty() {
+            metas.push(Meta::Obsoletes(to_set(&self.obsoletes)));
+        }
+        if !self.subseries.is_empty() {
+            metas.push(Meta::AlsoKnownAs(to_set(&self.subseries)));
+        }
+
+        metas
+    }
+
+    /* left column: "Key: value" fields, Internet-Draft marker and continued relation lists */
+    fn parse_left(&mut self, column: usize, text: &str, relation: &mut Option<TextRelation>) {
+        let (key, value) = match text.split_once(':') {
+            Some((key, value)) => (key.trim().to_lowercase(), value.trim()),
+            None => {
+                if let Some(stream) = stream_name(text) {
+                    self.stream = Some(stream);
+                    return;
+                }
+                match *relation {
+                    Some(current) if column > 0 => self.push_relation(current, rfc_list(text)),
+                    _ if text.eq_ignore_ascii_case("internet-draft") => self.is_rfc = false,
+                    _ => {}
+                }
+                return;
+            }
+        };
+
+        *relation = None;
+        match key.as_str() {
+            "request for comments" => {
+                if let Some(number) = rfc_list(value).into_iter().next() {
+                    self.id = Some(number);
+                    self.is_rfc = true;
+                }
+            }
+            "updates" => *relation = Some(TextRelation::Updates),
+            "obsoletes" => *relation = Some(TextRelation::Obsoletes),
+            "category" | "intended status" => self.status = Some(value.to_string()),
+            "issn" => {
+                self.issn = Some(value.to_string());
+                self.is_rfc = true;
+            }
+            "expires" => self.expires = parse_date(value).or_else(|| Some(value.to_string())),
+            subseries @ ("std" | "bcp" | "fyi") => {
+                if let Some(number) = numbers().find(value) {
+                    self.subseries.extend(DocIdentifier::from_number(subseries, number.as_str()));
+                }
+            }
+            _ => {}
+        }
+
+        if let Some(current) = *relation {
+            self.push_relation(current, rfc_list(value));
+        }
+    }
+
+    /* right column: authors, their organizations and the publication date */
+    fn parse_right(&mut self, text: &str) {
+        if let Some(date) = parse_date(text) {
+            self.date = Some(date);
+        } else if is_author(text) {
+            self.authors.push(text.to_string());
+        }
+    }
+
+    /* title paragraph below the header, drafts have their name right below the title */
+    fn parse_title(&mut self, lines: &[&str]) {
+        let mut title: Vec<&str> = Vec::new();
+        let mut title_done = false;
+        for line in lines.iter().map(|line| line.trim()) {
+            if let Some((name, revision)) = draft_name(line) {
+                self.id = name_to_id(name).ok();
+                self.revision = Some(revision);
+                self.is_rfc = false;
+                title_done |= !title.is_empty();
+            } else if line.starts_with("Abstract") || line.starts_with("Status of") {
+                break;
+            } else if line.is_empty() {
+                title_done |= !title.is_empty();
+            } else if !title_done {
+                title.push(line);
+            }
+        }
+
+        if !title.is_empty() {
+            self.title = Some(title.join(" "));
+        }
+    }
+}
+
+/* paragraphs of the "Abstract" section, ends at the next section title (unindented line) */
+fn parse_abstract(lines: &[&str]) -> Option<String> {
+    let start = lines.iter().position(|line| line.trim() == "Abstract")? + 1;
+    let mut paragraphs: Vec<String> = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    for line in &lines[start..] {
+        if !line.is_empty() && !line.starts_with(' ') {
+            break;
+        }
+
+        let line = line.trim();
+        if !line.is_empty() {
+            paragraph.push(line);
+        } else if !paragraph.is_empty() {
+            paragraphs.push(paragraph.join(" "));
+            paragraph.clear();
+        }
+    }
+    if !paragraph.is_empty() {
+        paragraphs.push(paragraph.join(" "));
+    }
+
+    Some(paragraphs.join("\n\n")).filter(|text| !text.is_empty())
+}
+
+/* parse the header block (first lines up to an empty line) and the title of a plain-text rfc or draft */
+pub fn parse_text_header(text: &str) -> TextHeader {
+    let mut header = TextHeader::default();
+
+    let lines: Vec<&str> = text
+        .lines()
+        .map(|line| line.trim_end_matches(['\r', '\x0c']))
+        .collect();
+    let start = match lines.iter().position(|line| !line.trim().is_empty()) {
+        Some(start) => start,
+        None => return header,
+    };
+    let end = lines[start..]
+        .iter()
+        .position(|line| line.trim().is_empty())
+        .map_or(lines.len(), |length| start + length);
+
+    let mut relation = None;
+    for line in &lines[start..end] {
+        for (column, text) in columns(line) {
+            if column < RIGHT_COLUMN {
+                header.parse_left(column, text, &mut relation);
+            } else {
+                header.parse_right(text);
+            }
+        }
+    }
+
+    // the title is the first paragraph after the header, the draft name may be right below it
+    let title_end = lines.len().min(end + 20);
+    header.parse_title(&lines[end..title_end]);
+    header.abstract_text = parse_abstract(&lines[end..]);
+
+    let form_feeds = text.matches('\x0c').count();
+    if form_feeds > 0 {
+        // the last page may or may not end with a form feed
+        let last_page = text.rsplit('\x0c').next().is_some_and(|page| !page.trim().is_empty());
+        header.pages = Some((form_feeds + last_page as usize) as u32);
+    }
+
+    header
+}
+
+impl<C> IetfDoc<C>
+    where
+        C: IdContainer,
+{
+    /* build a document from its plain text only (eg: a downloaded rfc or draft) */
+    pub fn from_text(text: &str) -> Result<IetfDoc<C>> {
+        let header = parse_text_header(text);
+        let mut meta: MetaMap<C> = MetaMap::default();
+        for relation in header.metas() {
+            meta.push_meta(relation);
+        }
+
+        let id = match header.id {
+            Some(ref id) => id.clone(),
+            None => return Query("no document name in text header".to_string()).into(),
+        };
+
+        let details = header.details();
+        let mut summary = Summary::new(
+            id,
+            header.revision.unwrap_or_else(|| "00".to_string()),
+            header.is_rfc,
+            header.title.unwrap_or_default(),
+        )?;
+        summary.complete(details);
+
+        Ok(IetfDoc {
+            summary,
+            meta,
+            revisions: Vec::new(),
+            errata: None,
+        })
+    }
+
+    /* complete the summary fields and metas missing from a document using its plain text
+     * known fields and metas are kept, returns the number of fields and metas added */
+    pub fn update_from_text(&mut self, text: &str) -> usize {
+        let header = parse_text_header(text);
+        let metas = header.metas::<C>();
+        let mut added = self.summary.complete(header.details());
+        if self.summary.title.is_empty() {
+            if let Some(title) = header.title {
+                self.summary.title = title;
+                added += 1;
+            }
+        }
+
+        for meta in metas {
+            let known = self
+                .meta
+                .values()
+                .any(|known| mem::discriminant(known) == mem::discriminant(&meta));
+            if !known {
+                self.meta.push_meta(meta);
+                added += 1;
+            }
+        }
+
+        added
+    }
+}
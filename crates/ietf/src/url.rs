@@ -1,47 +1,123 @@
 use crate::error::{DocError, Result};
+use crate::{DocFormat, DocIdentifier};
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 use std::str::FromStr;
+use std::sync::{PoisonError, RwLock};
 use url::Url;
 
+/* Base urls documents are fetched from, each one may point to a mirror
+ * local directories are given as file:// urls (see UrlConfig::parse_base) */
+#[derive(Debug, Clone)]
+pub struct UrlConfig {
+    // datatracker pages and api: metadata, references, lookup
+    pub datatracker: Url,
+    // rfc archive holding rfcXXXX.xml, rfcXXXX.txt, rfcXXXX.html and rfcXXXX.pdf
+    pub rfc_archive: Url,
+    // internet-draft archive holding draft-xxx-NN.xml, .txt, .html and .pdf
+    pub draft_archive: Url,
+    // rfc-editor site holding the errata dump (errata.json)
+    pub errata: Url,
+}
+
+impl Default for UrlConfig {
+    fn default() -> Self {
+        let base = |url: &str| Url::from_str(url).expect("valid default base url");
+        Self {
+            datatracker: base("https://datatracker.ietf.org/"),
+            rfc_archive: base("https://www.rfc-editor.org/rfc/"),
+            draft_archive: base("https://www.ietf.org/archive/id/"),
+            errata: base("https://www.rfc-editor.org/"),
+        }
+    }
+}
+
+impl UrlConfig {
+    /* base url from an url or a local directory, a trailing '/' is added so that documents are joined to it */
+    pub fn parse_base(value: &str) -> Result<Url> {
+        let mut url = match Url::from_str(value) {
+            Ok(url) => url,
+            Err(_) => {
+                let path = Path::new(value)
+                    .canonicalize()
+                    .map_err(|err| DocError::Url(format!("wrong base url {}: {}", value, err)))?;
+                Url::from_directory_path(&path)
+                    .map_err(|_| DocError::Url(format!("wrong base directory {}", path.display())))?
+            }
+        };
+
+        if !url.path().ends_with('/') {
+            url.set_path(&format!("{}/", url.path()));
+        }
+
+        Ok(url)
+    }
+}
+
+/* Base urls used by every SourceUrl, defaults to the IETF services */
+static URL_CONFIG: RwLock<Option<UrlConfig>> = RwLock::new(None);
+
+pub fn set_url_config(config: UrlConfig) {
+    *URL_CONFIG.write().unwrap_or_else(PoisonError::into_inner) = Some(config);
+}
+
+pub fn url_config() -> UrlConfig {
+    URL_CONFIG
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+        .unwrap_or_default()
+}
+
+/* urls of a document, built from the current UrlConfig when requested */
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SourceUrl {
-    id: String,
-    html: Url,
+    id: DocIdentifier,
 }
 
 impl SourceUrl {
-    pub fn html(&self) -> &Url {
-        &self.html
+    pub fn html(&self) -> Result<Url> {
+        Ok(url_config().datatracker.join(&format!("doc/{}", self.id))?)
     }
 
-    pub fn xml(&self, is_rfc: bool) -> Result<Url> {
-        Ok(if is_rfc {
-            Url::from_str(format!("https://www.rfc-editor.org/rfc/{}.xml", self.id).as_str())?
+    fn archive(&self, is_rfc: bool) -> Url {
+        let config = url_config();
+        if is_rfc {
+            config.rfc_archive
         } else {
-            Url::from_str(format!("https://www.ietf.org/archive/id/{}.xml", self.id).as_str())?
-        })
+            config.draft_archive
+        }
+    }
+
+    pub fn xml(&self, is_rfc: bool) -> Result<Url> {
+        Ok(self.archive(is_rfc).join(&format!("{}.xml", self.id))?)
     }
 
     pub fn raw(&self, is_rfc: bool) -> Result<Url> {
-        Ok(if is_rfc {
-            Url::from_str(format!("https://www.rfc-editor.org/rfc/{}.txt", self.id).as_str())?
-        } else {
-            Url::from_str(format!("https://www.ietf.org/archive/id/{}.txt", self.id).as_str())?
-        })
+        Ok(self.archive(is_rfc).join(&format!("{}.txt", self.id))?)
     }
 
-    pub fn new(id: &String) -> Result<Self> {
-        Ok(Self {
-            id: id.clone(),
-            html: Url::from_str(format!("https://datatracker.ietf.org/doc/{}", id).as_str())?,
-        })
+    /* copy of the document in the archive, raw and xml are its txt and xml copies */
+    pub fn format(&self, is_rfc: bool, format: DocFormat) -> Result<Url> {
+        Ok(self.archive(is_rfc).join(&format!("{}.{}", self.id, format.extension()))?)
+    }
+
+    pub fn references(&self) -> Result<Url> {
+        Ok(url_config().datatracker.join(&format!("doc/{}/references/", self.id))?)
+    }
+
+    pub fn referenced_by(&self) -> Result<Url> {
+        Ok(url_config().datatracker.join(&format!("doc/{}/referencedby/", self.id))?)
+    }
+
+    pub fn new(id: &DocIdentifier) -> Result<Self> {
+        let url = Self { id: id.clone() };
+        url.html()?;
+
+        Ok(url)
     }
 
-    pub fn get_id(&self) -> Result<&str> {
-        self.html
-            .path()
-            .rsplit_terminator('/')
-            .next()
-            .ok_or_else(|| DocError::Url(format!("wrong url {}", self.html)))
+    pub fn get_id(&self) -> &DocIdentifier {
+        &self.id
     }
 }
@@ -0,0 +1,345 @@
+use crate::doc::{group_from_name, SummaryDetails};
+use crate::error::{DocError, Result};
+use crate::index::format_date;
+use crate::meta::Meta;
+use crate::{name_to_id, DocIdentifier, IdContainer, MetaMap};
+use fast_xml::events::{BytesStart, Event};
+use fast_xml::Reader;
+use regex::Regex;
+use std::collections::HashSet;
+use std::sync::{Arc, OnceLock};
+use url::Url;
+
+/* kind of the <references> section being parsed */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReferencesKind {
+    Normative,
+    Informative,
+}
+
+impl ReferencesKind {
+    fn from_title(title: &str) -> Option<ReferencesKind> {
+        let title = title.to_lowercase();
+        if title.contains("normative") {
+            Some(ReferencesKind::Normative)
+        } else if title.contains("informative") {
+            Some(ReferencesKind::Informative)
+        } else {
+            None
+        }
+    }
+}
+
+/* a <reference> element being parsed */
+#[derive(Debug, Default)]
+struct XmlReference {
+    anchor: Option<String>,
+    target: Option<String>,
+    series: Vec<(String, String)>,
+}
+
+/* the pattern is compiled once, on the first capture with its regex */
+fn capture(regex: &OnceLock<Regex>, pattern: &str, text: &str) -> Option<Vec<String>> {
+    let captures = regex.get_or_init(|| Regex::new(pattern).unwrap()).captures(text)?;
+    Some(
+        captures
+            .iter()
+            .skip(1)
+            .map(|c| c.map(|c| c.as_str().to_string()).unwrap_or_default())
+            .collect(),
+    )
+}
+
+/* rfc-editor info / datatracker document urls */
+fn id_from_url(url: &str) -> Option<DocIdentifier> {
+    static RFC_URL: OnceLock<Regex> = OnceLock::new();
+    static DATATRACKER_URL: OnceLock<Regex> = OnceLock::new();
+
+    if let Some(c) = capture(&RFC_URL, r"(?i)rfc-editor\.org/(?:info|rfc)/(rfc|bcp|std|fyi)0*(\d+)", url) {
+        return DocIdentifier::from_number(&c[0], &c[1]);
+    }
+    let datatracker = r"(?i)datatracker\.ietf\.org/doc/(?:html/)?((?:draft|rfc|bcp|std|fyi)[\w.-]*?)/?$";
+    capture(&DATATRACKER_URL, datatracker, url)
+        .and_then(|c| name_to_id(&c[0]).ok())
+}
+
+/* anchors of the bibxml references: RFC2119, I-D.ietf-foo-bar, BCP14 */
+fn id_from_anchor(anchor: &str) -> Option<DocIdentifier> {
+    static SERIES_ANCHOR: OnceLock<Regex> = OnceLock::new();
+    static DRAFT_ANCHOR: OnceLock<Regex> = OnceLock::new();
+
+    if let Some(c) = capture(&SERIES_ANCHOR, r"^(?i)(RFC|BCP|STD|FYI)0*(\d+)$", anchor) {
+        return DocIdentifier::from_number(&c[0], &c[1]);
+    }
+    capture(&DRAFT_ANCHOR, r"^I-D\.(?:draft-)?(.+)$", anchor)
+        .and_then(|c| name_to_id(format!("draft-{}", c[0])).ok())
+}
+
+/* bibxml includes: .../reference.RFC.2119.xml, .../reference.I-D.ietf-foo-bar.xml */
+fn id_from_include(href: &str) -> Option<DocIdentifier> {
+    static SERIES_INCLUDE: OnceLock<Regex> = OnceLock::new();
+    static DRAFT_INCLUDE: OnceLock<Regex> = OnceLock::new();
+
+    if let Some(c) = capture(&SERIES_INCLUDE, r"reference\.(RFC|BCP|STD|FYI)\.0*(\d+)\.xml$", href) {
+        return DocIdentifier::from_number(&c[0], &c[1]);
+    }
+    capture(&DRAFT_INCLUDE, r"reference\.I-D\.(?:draft-)?(.+)\.xml$", href)
+        .and_then(|c| name_to_id(format!("draft-{}", c[0])).ok())
+}
+
+impl XmlReference {
+    fn series(&self, name: &str) -> Option<&str> {
+        self.series
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.trim())
+    }
+
+    /* a reference may point to an rfc, a draft or a subseries, anything else (ieee, ...) is ignored */
+    fn to_id(&self) -> Option<DocIdentifier> {
+        if let Some(id) = self.series("RFC").and_then(|num| DocIdentifier::from_number("rfc", num)) {
+            return Some(id);
+        }
+        if let Some(draft) = self.series("Internet-Draft").and_then(|draft| name_to_id(draft).ok()) {
+            return Some(draft);
+        }
+        if let Some(id) = self
+            .target
+            .as_deref()
+            .and_then(id_from_url)
+            .or_else(|| self.anchor.as_deref().and_then(id_from_anchor))
+        {
+            return Some(id);
+        }
+
+        // subseries only when the reference doesn't point to a specific document
+        ["BCP", "STD", "FYI"]
+            .into_iter()
+            .find_map(|subseries| self.series(subseries).and_then(|num| DocIdentifier::from_number(subseries, num)))
+    }
+}
+
+fn attribute(element: &BytesStart, key: &[u8]) -> Option<String> {
+    element
+        .attributes()
+        .filter_map(|a| a.ok())
+        .find(|a| a.key == key)
+        .map(|a| String::from_utf8_lossy(&a.value).to_string())
+}
+
+fn xml_error(url: &Url, err: impl Into<fast_xml::Error>) -> DocError {
+    DocError::Xml {
+        url: url.to_string(),
+        source: Some(Arc::new(err.into())),
+    }
+}
+
+/* parse the metas of a document from its XML (v2 or v3)
+ * relations are read from the attributes of <rfc>
+ * references are read from the <reference> and <referencegroup> of the normative / informative <references> sections */
+pub(crate) fn parse_meta_xml<C: IdContainer>(url: &Url, bytes: &[u8]) -> Result<MetaMap<C>> {
+    let mut xml = Reader::from_bytes(bytes);
+    let mut buf = Vec::new();
+    let mut metas: MetaMap<C> = MetaMap::default();
+
+    // kinds of the nested <references> sections, None until their <name> is read
+    let mut sections: Vec<Option<ReferencesKind>> = Vec::new();
+    let mut section_name: Option<String> = None;
+    let mut reference: Option<XmlReference> = None;
+
+    let mut normative: HashSet<DocIdentifier> = HashSet::new();
+    let mut informative: HashSet<DocIdentifier> = HashSet::new();
+    let mut add_reference = |sections: &Vec<Option<ReferencesKind>>, id: DocIdentifier| {
+        // unclassified references are considered informative
+        match sections.iter().rev().find_map(|kind| *kind) {
+            Some(ReferencesKind::Normative) => normative.insert(id),
+            _ => informative.insert(id),
+        };
+    };
+
+    loop {
+        match xml.read_event(&mut buf) {
+            Ok(Event::Start(ref e)) if e.name() == b"rfc" => {
+                for attribute in e.attributes() {
+                    let attribute = attribute.map_err(|err| xml_error(url, err))?;
+                    // other attributes (docName, category, ipr...) aren't relations
+                    if let Ok(meta) = Meta::from_xml(&attribute) {
+                        metas.push_meta(meta);
+                    }
+                }
+            }
+            Ok(Event::Start(ref e)) if e.name() == b"references" => {
+                // v2 sections have a title attribute, v3 sections have a <name> child
+                let kind = attribute(e, b"title").and_then(|title| ReferencesKind::from_title(&title));
+                sections.push(kind);
+            }
+            Ok(Event::End(ref e)) if e.name() == b"references" => {
+                sections.pop();
+            }
+            Ok(Event::Start(ref e))
+                if e.name() == b"name" && !sections.is_empty() && reference.is_none() =>
+            {
+                section_name = Some(String::new());
+            }
+            Ok(Event::Text(ref e)) if section_name.is_some() => {
+                if let Some(name) = section_name.as_mut() {
+                    name.push_str(&String::from_utf8_lossy(e.escaped()));
+                }
+            }
+            Ok(Event::End(ref e)) if e.name() == b"name" && section_name.is_some() => {
+                let name = section_name.take().unwrap_or_default();
+                if let Some(kind @ None) = sections.last_mut() {
+                    *kind = ReferencesKind::from_title(&name);
+                }
+            }
+            // the subseries of a group of references (BCP14: RFC2119 and RFC8174) is referenced along with them
+            Ok(Event::Start(ref e)) if e.name() == b"referencegroup" && !sections.is_empty() => {
+                let id = attribute(e, b"anchor")
+                    .and_then(|anchor| id_from_anchor(&anchor))
+                    .or_else(|| attribute(e, b"target").and_then(|target| id_from_url(&target)));
+                if let Some(id) = id {
+                    add_reference(&sections, id);
+                }
+            }
+            Ok(Event::Start(ref e)) if e.name() == b"reference" && !sections.is_empty() => {
+                reference = Some(XmlReference {
+                    anchor: attribute(e, b"anchor"),
+                    target: attribute(e, b"target"),
+                    series: Vec::new(),
+                });
+            }
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) if e.name() == b"seriesInfo" => {
+                if let (Some(reference), Some(name), Some(value)) = (
+                    reference.as_mut(),
+                    attribute(e, b"name"),
+                    attribute(e, b"value"),
+                ) {
+                    reference.series.push((name, value));
+                }
+            }
+            Ok(Event::End(ref e)) if e.name() == b"reference" => {
+                if let Some(id) = reference.take().and_then(|r| r.to_id()) {
+                    add_reference(&sections, id);
+                }
+            }
+            Ok(Event::Empty(ref e)) if e.name() == b"xi:include" && !sections.is_empty() => {
+                if let Some(id) = attribute(e, b"href").and_then(|href| id_from_include(&href)) {
+                    add_reference(&sections, id);
+                }
+            }
+            Ok(Event::Eof) => {
+                break;
+            }
+            Ok(_) => {}
+            Err(err) => return Err(xml_error(url, err)),
+        }
+        buf.clear();
+    }
+
+    let to_set = |ids: HashSet<DocIdentifier>| ids.into_iter().map(C::Holder::from).collect();
+    if !normative.is_empty() {
+        metas.push_meta(Meta::NormativeReferences(to_set(normative)));
+    }
+    if !informative.is_empty() {
+        metas.push_meta(Meta::InformativeReferences(to_set(informative)));
+    }
+
+    Ok(metas)
+}
+
+/* category attribute of <rfc>, intended status of drafts */
+fn category_name(category: &str) -> String {
+    match category {
+        "std" => "Standards Track",
+        "bcp" => "Best Current Practice",
+        "info" => "Informational",
+        "exp" => "Experimental",
+        "historic" => "Historic",
+        category => category,
+    }
+    .to_string()
+}
+
+/* summary details from the <rfc> attributes and the <front> of a document XML (v2 or v3)
+ * the <front> of references is skipped */
+pub(crate) fn parse_summary_xml(url: &Url, bytes: &[u8]) -> Result<SummaryDetails> {
+    let mut xml = Reader::from_bytes(bytes);
+    let mut buf = Vec::new();
+    let mut details = SummaryDetails::default();
+
+    let mut in_front = false;
+    let mut in_reference = false;
+    // paragraphs of the abstract, None outside of <abstract>
+    let mut abstract_text: Option<Vec<String>> = None;
+
+    loop {
+        match xml.read_event(&mut buf) {
+            Ok(Event::Start(ref e)) if e.name() == b"rfc" => {
+                details.status = attribute(e, b"category").map(|category| category_name(&category));
+                details.stream = attribute(e, b"submissionType").map(|stream| match stream.as_str() {
+                    "independent" => "ISE".to_string(),
+                    stream => stream.to_uppercase(),
+                });
+                details.group = attribute(e, b"docName")
+                    .and_then(|name| name_to_id(name).ok())
+                    .and_then(|id| group_from_name(&id));
+            }
+            Ok(Event::Start(ref e)) if e.name() == b"reference" => in_reference = true,
+            Ok(Event::End(ref e)) if e.name() == b"reference" => in_reference = false,
+            Ok(Event::Start(ref e)) if e.name() == b"front" && !in_reference => in_front = true,
+            Ok(Event::End(ref e)) if e.name() == b"front" && !in_reference => in_front = false,
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) if in_front && e.name() == b"author" => {
+                let name = attribute(e, b"fullname").or_else(|| {
+                    let surname = attribute(e, b"surname")?;
+                    Some(match attribute(e, b"initials") {
+                        Some(initials) => format!("{} {}", initials, surname),
+                        None => surname,
+                    })
+                });
+                details.authors.extend(name);
+            }
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e))
+                if in_front && e.name() == b"date" && details.date.is_none() =>
+            {
+                if let Some(year) = attribute(e, b"year") {
+                    let (month, day) = (attribute(e, b"month"), attribute(e, b"day"));
+                    details.date = Some(format_date(&year, month.as_deref(), day.as_deref()));
+                }
+            }
+            Ok(Event::Start(ref e)) if in_front && e.name() == b"abstract" => {
+                abstract_text = Some(Vec::new());
+            }
+            Ok(Event::Start(ref e)) if e.name() == b"t" => {
+                if let Some(paragraphs) = abstract_text.as_mut() {
+                    paragraphs.push(String::new());
+                }
+            }
+            Ok(Event::Text(ref e)) => {
+                if let Some(paragraph) = abstract_text.as_mut().and_then(|paragraphs| paragraphs.last_mut()) {
+                    let text = e
+                        .unescape_and_decode(&xml)
+                        .unwrap_or_else(|_| String::from_utf8_lossy(e.escaped()).to_string());
+                    paragraph.push_str(&text);
+                }
+            }
+            Ok(Event::End(ref e)) if e.name() == b"abstract" => {
+                let paragraphs: Vec<String> = abstract_text
+                    .take()
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|paragraph| paragraph.split_whitespace().collect::<Vec<_>>().join(" "))
+                    .filter(|paragraph| !paragraph.is_empty())
+                    .collect();
+                details.abstract_text = Some(paragraphs.join("\n\n")).filter(|text| !text.is_empty());
+            }
+            // the <front> is over once the first section starts
+            Ok(Event::Start(ref e)) if e.name() == b"middle" => break,
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(err) => return Err(xml_error(url, err)),
+        }
+        buf.clear();
+    }
+
+    Ok(details)
+}
@@ -0,0 +1,34 @@
+#![allow(dead_code)]
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use rfc_dep_ietf::{DocIdentifier, FixtureSource, IdContainer, IetfDoc, Meta};
+
+/* documents holding plain ids */
+#[derive(Debug)]
+pub struct Ids;
+
+impl IdContainer for Ids {
+    type Holder<T> = DocIdentifier;
+}
+
+pub fn fixtures_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+}
+
+pub fn fixtures() -> FixtureSource {
+    FixtureSource::new(fixtures_dir())
+}
+
+pub fn ids(names: &[&str]) -> HashSet<DocIdentifier> {
+    names.iter().map(|name| name.parse().unwrap()).collect()
+}
+
+/* ids of the meta picked from a document, empty if the document doesn't have it */
+pub fn meta_ids(
+    doc: &IetfDoc<Ids>,
+    pick: impl Fn(&Meta<Ids>) -> Option<&HashSet<DocIdentifier>>,
+) -> HashSet<DocIdentifier> {
+    doc.meta.values().find_map(pick).cloned().unwrap_or_default()
+}
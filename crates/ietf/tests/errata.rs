@@ -0,0 +1,73 @@
+mod common;
+
+use common::{fixtures, fixtures_dir, Ids};
+use rfc_dep_ietf::error::DocError;
+use rfc_dep_ietf::{
+    fetch_errata_with, load_errata, parse_errata, DocIdentifier, Erratum, ErratumStatus, ErratumType, IetfDoc,
+};
+
+#[test]
+fn errata_dump() {
+    let errata = load_errata(fixtures_dir().join("www.rfc-editor.org/errata.json")).unwrap();
+
+    // entries of other documents are skipped
+    assert_eq!(errata.len(), 2);
+
+    // sorted by id
+    let rfc9293 = &errata[&DocIdentifier::Rfc(9293)];
+    let ids: Vec<u32> = rfc9293.iter().map(|erratum| erratum.id).collect();
+    assert_eq!(ids, vec![7129, 7144]);
+
+    let verified = &rfc9293[0];
+    assert_eq!(verified.kind, ErratumType::Technical);
+    assert_eq!(verified.status, ErratumStatus::Verified);
+    assert_eq!(verified.section.as_deref(), Some("3.10.7.4"));
+    // texts are trimmed but keep their line breaks
+    assert_eq!(
+        verified.original.as_deref(),
+        Some("If the RST bit is set then, any outstanding RECEIVEs and SEND\n   should receive \"reset\" responses.")
+    );
+
+    // ids may be numbers, empty fields are None
+    let held = &errata[&DocIdentifier::Rfc(2119)][0];
+    assert_eq!(held.id, 5604);
+    assert_eq!(held.status, ErratumStatus::HeldForDocumentUpdate);
+    assert_eq!(held.section, None);
+    assert_eq!(held.original, None);
+}
+
+#[test]
+fn errata_counts() {
+    let errata = fetch_errata_with(&fixtures()).unwrap();
+    let mut doc = IetfDoc::<Ids>::from_text("Request for Comments: 9293\n\n   TCP\n").unwrap();
+
+    assert_eq!(doc.errata_count(ErratumStatus::Verified), None);
+    assert_eq!(doc.update_errata(&errata), 2);
+    assert_eq!(doc.errata_count(ErratumStatus::Verified), Some(1));
+    assert_eq!(doc.errata_count(ErratumStatus::Reported), Some(1));
+    assert_eq!(doc.errata_count(ErratumStatus::Rejected), Some(0));
+}
+
+#[test]
+fn malformed_errata() {
+    let missing_id = br#"[{"doc-id": "RFC1", "errata_status_code": "Verified"}]"#;
+    assert!(matches!(parse_errata(missing_id, "errata.json"), Err(DocError::Json { .. })));
+
+    let unknown_status = br#"[{"errata_id": "1", "doc-id": "RFC1", "errata_status_code": "Lost"}]"#;
+    assert!(matches!(parse_errata(unknown_status, "errata.json"), Err(DocError::Json { .. })));
+
+    assert!(matches!(parse_errata(b"{}", "errata.json"), Err(DocError::Json { .. })));
+    assert!(matches!(parse_errata(b"not json", "errata.json"), Err(DocError::Json { .. })));
+}
+
+#[test]
+fn errata_of_older_projects() {
+    let erratum: Erratum = serde_json::from_str(
+        r#"{"id": 7129, "tyype": "Technical", "status": "Verified", "section": null, "original": null, "corrected": null}"#,
+    )
+    .unwrap();
+    assert_eq!(erratum.kind, ErratumType::Technical);
+
+    let saved = serde_json::to_value(&erratum).unwrap();
+    assert_eq!(saved["kind"], "Technical");
+}
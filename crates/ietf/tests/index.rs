@@ -0,0 +1,109 @@
+mod common;
+
+use common::{fixtures_dir, ids, meta_ids, Ids};
+use rfc_dep_ietf::{load_rfc_index, parse_rfc_index, IetfDoc, Meta};
+
+fn index() -> Vec<IetfDoc<Ids>> {
+    load_rfc_index(fixtures_dir().join("www.rfc-editor.org/rfc-index.xml")).unwrap()
+}
+
+fn entry<'a>(docs: &'a [IetfDoc<Ids>], name: &str) -> &'a IetfDoc<Ids> {
+    docs.iter()
+        .find(|doc| doc.summary.id.to_string() == name)
+        .unwrap_or_else(|| panic!("{} not in index", name))
+}
+
+#[test]
+fn index_entries() {
+    let docs = index();
+    let mut names: Vec<String> = docs.iter().map(|doc| doc.summary.id.to_string()).collect();
+    names.sort();
+
+    // not issued rfcs are skipped
+    assert_eq!(names, vec!["bcp14", "rfc2119", "rfc9293", "std7"]);
+}
+
+#[test]
+fn index_rfc_summary() {
+    let docs = index();
+    let summary = &entry(&docs, "rfc9293").summary;
+
+    assert!(summary.is_rfc);
+    assert_eq!(summary.title, "Transmission Control Protocol (TCP)");
+    // revision of the draft the rfc was published from
+    assert_eq!(summary.revision, "28");
+    assert_eq!(summary.status.as_deref(), Some("INTERNET STANDARD"));
+    assert_eq!(summary.date.as_deref(), Some("2022-08"));
+    assert_eq!(summary.authors, vec!["W. Eddy"]);
+    assert_eq!(summary.stream.as_deref(), Some("IETF"));
+    assert_eq!(summary.group.as_deref(), Some("tcpm"));
+    assert_eq!(summary.pages, Some(98));
+    let abstract_text = summary.abstract_text.as_deref().unwrap();
+    assert_eq!(abstract_text.split("\n\n").count(), 2);
+
+    // rfcs outside of any working group have none
+    let summary = &entry(&docs, "rfc2119").summary;
+    assert_eq!(summary.group, None);
+    assert_eq!(summary.revision, "00");
+}
+
+#[test]
+fn index_rfc_metas() {
+    let docs = index();
+    let doc = entry(&docs, "rfc9293");
+
+    let obsoletes = meta_ids(doc, |meta| match meta {
+        Meta::Obsoletes(set) => Some(set),
+        _ => None,
+    });
+    assert_eq!(obsoletes, ids(&["rfc793", "rfc879", "rfc6093"]));
+    let updates = meta_ids(doc, |meta| match meta {
+        Meta::Updates(set) => Some(set),
+        _ => None,
+    });
+    assert_eq!(updates, ids(&["rfc1011", "rfc1122", "rfc5961"]));
+    let also_known_as = meta_ids(doc, |meta| match meta {
+        Meta::AlsoKnownAs(set) => Some(set),
+        _ => None,
+    });
+    assert_eq!(also_known_as, ids(&["std7"]));
+
+    let updated_by = meta_ids(entry(&docs, "rfc2119"), |meta| match meta {
+        Meta::UpdatedBy(set) => Some(set),
+        _ => None,
+    });
+    assert_eq!(updated_by, ids(&["rfc8174"]));
+}
+
+#[test]
+fn index_subseries() {
+    let docs = index();
+
+    let bcp = entry(&docs, "bcp14");
+    assert_eq!(bcp.summary.title, "BCP 14");
+    let contains = meta_ids(bcp, |meta| match meta {
+        Meta::Contains(set) => Some(set),
+        _ => None,
+    });
+    assert_eq!(contains, ids(&["rfc2119", "rfc8174"]));
+
+    assert_eq!(entry(&docs, "std7").summary.title, "Transmission Control Protocol");
+}
+
+#[test]
+fn index_rfc_in_several_subseries() {
+    let index = br#"<rfc-index xmlns="https://www.rfc-editor.org/rfc-index">
+        <rfc-entry>
+            <doc-id>RFC5000</doc-id>
+            <title>Example</title>
+            <is-also><doc-id>STD0001</doc-id><doc-id>BCP0009</doc-id></is-also>
+        </rfc-entry>
+    </rfc-index>"#;
+    let docs: Vec<IetfDoc<Ids>> = parse_rfc_index(index).unwrap();
+
+    let also_known_as = meta_ids(entry(&docs, "rfc5000"), |meta| match meta {
+        Meta::AlsoKnownAs(set) => Some(set),
+        _ => None,
+    });
+    assert_eq!(also_known_as, ids(&["std1", "bcp9"]));
+}
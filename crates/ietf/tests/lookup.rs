@@ -0,0 +1,38 @@
+mod common;
+
+use common::{fixtures, Ids};
+use rfc_dep_ietf::error::DocError;
+use rfc_dep_ietf::{IetfDoc, LookupQuery};
+
+#[test]
+fn malformed_results_are_skipped() {
+    let source = fixtures();
+    let query = LookupQuery::new().title("flowspec");
+    let mut pages = query.pages(&source).unwrap();
+
+    let summaries: Vec<_> = pages.by_ref().collect::<Result<Vec<_>, _>>().unwrap().concat();
+    let names: Vec<String> = summaries.iter().map(|summary| summary.id.to_string()).collect();
+    assert_eq!(names, vec!["rfc8955", "rfc5575"]);
+
+    let rfc8955 = &summaries[0];
+    assert_eq!(rfc8955.title, "Dissemination of Flow Specification Rules");
+    assert_eq!(rfc8955.status.as_deref(), Some("Proposed Standard"));
+    assert_eq!(rfc8955.stream.as_deref(), Some("IETF"));
+    assert_eq!(rfc8955.group.as_deref(), Some("idr"));
+    assert_eq!(rfc8955.pages, Some(36));
+    // group of rfc5575 can't be queried
+    assert_eq!(summaries[1].group, None);
+
+    // rfc8956 has no title
+    let errors = pages.errors();
+    assert_eq!(errors.len(), 2);
+    assert!(errors.iter().any(|err| matches!(err, DocError::Json { .. })));
+    assert!(errors.iter().any(|err| matches!(err, DocError::NotFound { .. })));
+}
+
+#[test]
+fn lookup_skips_malformed_results() {
+    let query = LookupQuery::new().title("flowspec");
+    let summaries = IetfDoc::<Ids>::lookup_query_with(&fixtures(), &query).unwrap();
+    assert_eq!(summaries.len(), 2);
+}
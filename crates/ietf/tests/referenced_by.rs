@@ -0,0 +1,18 @@
+mod common;
+
+use common::{fixtures, Ids};
+use rfc_dep_ietf::{set_query_referenced_by, IetfDoc, Meta};
+
+// the setting is global, the other tests of the documents citing a document are in xml.rs
+#[test]
+fn referenced_by_not_queried() {
+    set_query_referenced_by(false);
+    let doc = IetfDoc::<Ids>::from_name_with(&fixtures(), "draft-ietf-idr-example").unwrap();
+
+    assert!(!doc
+        .meta
+        .values()
+        .any(|meta| matches!(meta, Meta::NormativelyReferencedBy(_) | Meta::InformativelyReferencedBy(_))));
+    // references are still read from the xml
+    assert!(doc.meta.values().any(|meta| matches!(meta, Meta::NormativeReferences(_))));
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use common::{fixtures, Ids};
+use rfc_dep_ietf::{
+    Conditional, ConditionalFuture, DocIdentifier, DocSource, FetchState, FixtureSource, IetfDoc, Refresh, SourceFuture, Validators,
+};
+use url::Url;
+
+/* fixtures counting the full queries of each url
+ * the pages whose path ends with 'modified' are reported as modified by conditional queries */
+#[derive(Debug)]
+struct Counting {
+    inner: FixtureSource,
+    modified: Option<&'static str>,
+    gets: Mutex<HashMap<String, usize>>,
+}
+
+impl Counting {
+    fn new(modified: Option<&'static str>) -> Self {
+        Counting {
+            inner: fixtures(),
+            modified,
+            gets: Mutex::default(),
+        }
+    }
+
+    fn gets(&self, url: &str) -> usize {
+        self.gets.lock().unwrap().get(url).copied().unwrap_or_default()
+    }
+
+    fn total(&self) -> usize {
+        self.gets.lock().unwrap().values().sum()
+    }
+}
+
+impl DocSource for Counting {
+    fn get<'a>(&'a self, url: &'a Url) -> SourceFuture<'a> {
+        *self.gets.lock().unwrap().entry(url.to_string()).or_default() += 1;
+        self.inner.get(url)
+    }
+
+    fn get_if_modified<'a>(&'a self, url: &'a Url, validators: &'a Validators) -> ConditionalFuture<'a> {
+        if self.modified.is_some_and(|path| url.path().ends_with(path)) {
+            return Box::pin(async move { Ok(Conditional::Modified(self.inner.get(url).await?)) });
+        }
+        self.inner.get_if_modified(url, validators)
+    }
+}
+
+const PAGE: &str = "https://datatracker.ietf.org/doc/draft-ietf-idr-example";
+const REFERENCED_BY: &str = "https://datatracker.ietf.org/doc/draft-ietf-idr-example-03/referencedby/";
+const XML: &str = "https://www.ietf.org/archive/id/draft-ietf-idr-example-03.xml";
+
+fn fetch() -> (IetfDoc<Ids>, FetchState) {
+    IetfDoc::<Ids>::fetch_with(&fixtures(), "draft-ietf-idr-example").unwrap()
+}
+
+/* the fixtures only have the page of the latest revision */
+fn id() -> DocIdentifier {
+    DocIdentifier::draft("draft-ietf-idr-example", None)
+}
+
+#[test]
+fn validators_are_recorded_per_url() {
+    let (doc, state) = fetch();
+
+    assert_eq!(doc.summary.id.to_string(), "draft-ietf-idr-example-03");
+    assert!(state.fetched_at.is_some());
+    // fixtures have their content hash as etag
+    for url in [PAGE, REFERENCED_BY, XML] {
+        assert!(state.validators[url].etag.is_some(), "no validators for {}", url);
+    }
+}
+
+#[test]
+fn unmodified_documents_are_kept() {
+    let (_, state) = fetch();
+    let source = Counting::new(None);
+
+    let refresh = IetfDoc::<Ids>::refresh_with(&source, &id(), &state).unwrap();
+    let Refresh::Unchanged(new_state) = refresh else {
+        panic!("expected the document to be unchanged");
+    };
+    assert_eq!(new_state.validators, state.validators);
+    assert!(new_state.fetched_at.is_some());
+    // only conditional queries
+    assert_eq!(source.total(), 0);
+}
+
+#[test]
+fn modified_responses_are_reused() {
+    let (doc, state) = fetch();
+    let source = Counting::new(Some("/doc/draft-ietf-idr-example"));
+
+    let refresh = IetfDoc::<Ids>::refresh_with(&source, &id(), &state).unwrap();
+    let Refresh::Changed(new_doc, new_state) = refresh else {
+        panic!("expected the document to be queried again");
+    };
+    assert_eq!(new_doc.summary.title, doc.summary.title);
+    assert_eq!(new_doc.meta.count(), doc.meta.count());
+    // the modified page is used as is, the other pages are queried again
+    assert_eq!(source.gets(PAGE), 0);
+    assert_eq!(source.gets(XML), 1);
+    assert_eq!(new_state.validators.keys().collect::<Vec<_>>(), state.validators.keys().collect::<Vec<_>>());
+}
+
+#[test]
+fn documents_without_validators_are_queried() {
+    let source = Counting::new(None);
+
+    let refresh = IetfDoc::<Ids>::refresh_with(&source, &id(), &FetchState::default()).unwrap();
+    assert!(matches!(refresh, Refresh::Changed(..)));
+    assert_eq!(source.gets(PAGE), 1);
+}
@@ -0,0 +1,19 @@
+mod common;
+
+use common::{fixtures, Ids};
+use rfc_dep_ietf::{DocIdentifier, IetfDoc};
+
+#[test]
+fn revision_history() {
+    let id = DocIdentifier::draft("draft-ietf-idr-example", Some(3));
+    let revisions = IetfDoc::<Ids>::revision_history_with(&fixtures(), &id).unwrap();
+
+    // oldest first, once each, revisions which aren't numbers are skipped
+    let names: Vec<&str> = revisions.iter().map(|revision| revision.revision.as_str()).collect();
+    assert_eq!(names, vec!["00", "01", "02", "03"]);
+    assert_eq!(revisions[1].date.as_deref(), Some("2022-09-12"));
+
+    // other documents have no revisions
+    let rfc = IetfDoc::<Ids>::revision_history_with(&fixtures(), &DocIdentifier::Rfc(9293)).unwrap();
+    assert!(rfc.is_empty());
+}
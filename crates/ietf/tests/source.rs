@@ -0,0 +1,120 @@
+mod common;
+
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::{env, fs, process};
+
+use common::{fixtures, Ids};
+use rfc_dep_ietf::error::DocError;
+use rfc_dep_ietf::{DocSource, FixtureSource, IetfDoc, RecordingSource, SourceResponse};
+use url::Url;
+
+fn url(url: &str) -> Url {
+    Url::from_str(url).unwrap()
+}
+
+#[test]
+fn path_for_maps_urls_to_fixture_files() {
+    let source = FixtureSource::new("/fixtures");
+
+    assert_eq!(
+        source.path_for(&url("https://www.rfc-editor.org/rfc/rfc9293.txt")),
+        PathBuf::from("/fixtures/www.rfc-editor.org/rfc/rfc9293.txt")
+    );
+    // paths without extension may also be directories
+    assert_eq!(
+        source.path_for(&url("https://datatracker.ietf.org/doc/rfc9293/")),
+        PathBuf::from("/fixtures/datatracker.ietf.org/doc/rfc9293/index")
+    );
+    assert_eq!(
+        source.path_for(&url("https://datatracker.ietf.org/api/v1/doc/document/?name__contains=tcp&format=json")),
+        PathBuf::from("/fixtures/datatracker.ietf.org/api/v1/doc/document/index_name__contains_tcp_format_json_0de2ad91785a6cb9")
+    );
+    assert_eq!(
+        source.path_for(&url("https://www.ietf.org/archive/id/draft-ietf-tcpm-rfc793bis-28.xml?v=1")),
+        PathBuf::from("/fixtures/www.ietf.org/archive/id/draft-ietf-tcpm-rfc793bis-28.xml_v_1_68535f194e3b3737")
+    );
+}
+
+#[test]
+fn path_for_tells_queries_apart() {
+    let source = FixtureSource::new("/fixtures");
+
+    // same sanitized query
+    let a = source.path_for(&url("https://datatracker.ietf.org/api/?a=b&c=d"));
+    let b = source.path_for(&url("https://datatracker.ietf.org/api/?a_b_c=d"));
+    assert_ne!(a, b);
+
+    // long queries (lookups with several filters) stay within the file name limits
+    let filters: Vec<String> = (0..50).map(|i| format!("filter{}__icontains=value{}", i, i)).collect();
+    let long = source.path_for(&url(&format!("https://datatracker.ietf.org/api/?{}", filters.join("&"))));
+    let name = long.file_name().unwrap().to_str().unwrap();
+    assert!(name.len() < 255, "{} is too long", name);
+    assert!(name.starts_with("index_filter0__icontains_value0_"));
+}
+
+#[tokio::test]
+async fn fixtures_are_served_for_their_url() {
+    let source = fixtures();
+    let target = url("https://www.rfc-editor.org/rfc/rfc9999.txt");
+
+    let resp = source.get(&target).await.unwrap();
+    assert_eq!(resp.url(), &target);
+    assert!(resp.text().unwrap().contains("rfc9999.txt"));
+
+    let query = url("https://datatracker.ietf.org/api/v1/doc/document/?name__contains=unknown&format=json");
+    let page: serde_json::Value = source.get(&query).await.unwrap().json().unwrap();
+    assert_eq!(page["meta"]["total_count"], 0);
+}
+
+#[tokio::test]
+async fn missing_fixtures_are_not_found() {
+    let err = fixtures()
+        .get(&url("https://www.rfc-editor.org/rfc/rfc1.txt"))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, DocError::NotFound { .. }));
+}
+
+#[tokio::test]
+async fn redirections_are_replayed() {
+    let resp = fixtures()
+        .get(&url("https://datatracker.ietf.org/doc/draft-unknown-doc/"))
+        .await
+        .unwrap();
+    assert_eq!(resp.url().path(), "/doc/search");
+    assert_eq!(
+        resp.url().query_pairs().find(|(key, _)| key == "name").unwrap().1,
+        "draft-unknown-doc"
+    );
+}
+
+#[test]
+fn redirections_to_the_search_are_not_found() {
+    // unknown documents are redirected to the datatracker search
+    let err = IetfDoc::<Ids>::from_name_with(&fixtures(), "draft-unknown-doc").unwrap_err();
+    assert!(matches!(err, DocError::NotFound { .. }));
+}
+
+#[tokio::test]
+async fn recorded_archives_are_replayed() {
+    let archive = env::temp_dir().join(format!("rfc-dep-record-{}", process::id()));
+    let recording = RecordingSource::new(fixtures(), &archive);
+
+    let redirected = url("https://datatracker.ietf.org/doc/draft-unknown-doc/");
+    let document = url("https://www.rfc-editor.org/rfc/rfc9999.txt");
+    let recorded: Vec<SourceResponse> = vec![
+        recording.get(&redirected).await.unwrap(),
+        recording.get(&document).await.unwrap(),
+    ];
+
+    let replay = FixtureSource::new(&archive);
+    for (target, recorded) in [&redirected, &document].into_iter().zip(recorded) {
+        let replayed = replay.get(target).await.unwrap();
+        assert_eq!(replayed.url(), recorded.url());
+        assert_eq!(replayed.bytes(), recorded.bytes());
+    }
+    assert!(archive.join("datatracker.ietf.org/doc/draft-unknown-doc/index.url").exists());
+
+    fs::remove_dir_all(archive).unwrap();
+}
@@ -0,0 +1,34 @@
+mod common;
+
+use common::{fixtures, ids, meta_ids, Ids};
+use rfc_dep_ietf::error::DocError;
+use rfc_dep_ietf::{DocIdentifier, IetfDoc, Meta};
+
+#[test]
+fn subseries_members() {
+    let found = IetfDoc::<Ids>::subseries_members_with(&fixtures(), &DocIdentifier::Bcp(14)).unwrap();
+
+    assert_eq!(found.members, vec![DocIdentifier::Rfc(2119), DocIdentifier::Rfc(8174)]);
+    // the charter isn't a document, the last object has no target
+    assert_eq!(found.errors.len(), 2);
+    assert!(matches!(found.errors[0], DocError::Identifier(_)));
+    assert!(matches!(found.errors[1], DocError::Json { .. }));
+
+    // other documents have no members
+    let rfc = IetfDoc::<Ids>::subseries_members_with(&fixtures(), &DocIdentifier::Rfc(2119)).unwrap();
+    assert!(rfc.members.is_empty());
+}
+
+#[test]
+fn subseries_document() {
+    let doc = IetfDoc::<Ids>::from_name_with(&fixtures(), "bcp14").unwrap();
+
+    assert_eq!(doc.summary.id, DocIdentifier::Bcp(14));
+    assert_eq!(doc.summary.title, "Key words for use in RFCs to Indicate Requirement Levels");
+    assert!(!doc.summary.is_rfc);
+    let members = meta_ids(&doc, |meta| match meta {
+        Meta::Contains(set) => Some(set),
+        _ => None,
+    });
+    assert_eq!(members, ids(&["rfc2119", "rfc8174"]));
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use std::fs;
+
+use common::{fixtures_dir, ids, meta_ids, Ids};
+use rfc_dep_ietf::{parse_text_header, IetfDoc, Meta};
+
+fn fixture(path: &str) -> String {
+    fs::read_to_string(fixtures_dir().join(path)).unwrap()
+}
+
+#[test]
+fn rfc_text_summary() {
+    let doc = IetfDoc::<Ids>::from_text(&fixture("www.rfc-editor.org/rfc/rfc8174.txt")).unwrap();
+    let summary = &doc.summary;
+
+    assert_eq!(summary.id.to_string(), "rfc8174");
+    assert!(summary.is_rfc);
+    assert_eq!(summary.title, "Ambiguity of Uppercase vs Lowercase in RFC 2119 Key Words");
+    assert_eq!(summary.status.as_deref(), Some("Best Current Practice"));
+    assert_eq!(summary.date.as_deref(), Some("2017-05"));
+    assert_eq!(summary.authors, vec!["B. Leiba"]);
+    assert_eq!(summary.stream.as_deref(), Some("IETF"));
+    assert_eq!(summary.pages, Some(2));
+    let abstract_text = summary.abstract_text.as_deref().unwrap();
+    assert!(abstract_text.starts_with("RFC 2119 specifies common key words"));
+    assert!(abstract_text.ends_with("defined special meanings."));
+}
+
+#[test]
+fn rfc_text_metas() {
+    let doc = IetfDoc::<Ids>::from_text(&fixture("www.rfc-editor.org/rfc/rfc8174.txt")).unwrap();
+
+    let updates = meta_ids(&doc, |meta| match meta {
+        Meta::Updates(set) => Some(set),
+        _ => None,
+    });
+    assert_eq!(updates, ids(&["rfc2119"]));
+    let also_known_as = meta_ids(&doc, |meta| match meta {
+        Meta::AlsoKnownAs(set) => Some(set),
+        _ => None,
+    });
+    assert_eq!(also_known_as, ids(&["bcp14"]));
+}
+
+#[test]
+fn draft_text_header() {
+    let header = parse_text_header(&fixture("www.ietf.org/archive/id/draft-ietf-tcpm-rfc793bis-28.txt"));
+
+    assert_eq!(header.id.as_ref().map(ToString::to_string).as_deref(), Some("draft-ietf-tcpm-rfc793bis-28"));
+    assert_eq!(header.revision.as_deref(), Some("28"));
+    assert!(!header.is_rfc);
+    assert_eq!(header.title.as_deref(), Some("Transmission Control Protocol (TCP)"));
+    assert_eq!(header.status.as_deref(), Some("Standards Track"));
+    assert_eq!(header.date.as_deref(), Some("2022-03-07"));
+    assert_eq!(header.expires.as_deref(), Some("2022-09-08"));
+    assert_eq!(header.authors, vec!["W. Eddy, Ed."]);
+    // not paginated
+    assert_eq!(header.pages, None);
+    assert_eq!(header.abstract_text.as_deref().map(|text| text.split("\n\n").count()), Some(2));
+
+    // relation lists continue on the next lines, "(if approved)" is ignored
+    let obsoletes: Vec<String> = header.obsoletes.iter().map(ToString::to_string).collect();
+    assert_eq!(obsoletes, vec!["rfc793", "rfc879", "rfc6093", "rfc6429", "rfc6528", "rfc6691"]);
+    let updates: Vec<String> = header.updates.iter().map(ToString::to_string).collect();
+    assert_eq!(updates, vec!["rfc1011", "rfc1122", "rfc5961"]);
+}
+
+#[test]
+fn draft_text_summary() {
+    let doc = IetfDoc::<Ids>::from_text(&fixture("www.ietf.org/archive/id/draft-ietf-tcpm-rfc793bis-28.txt")).unwrap();
+
+    assert_eq!(doc.summary.revision, "28");
+    // guessed from the draft name
+    assert_eq!(doc.summary.group.as_deref(), Some("tcpm"));
+    let obsoletes = meta_ids(&doc, |meta| match meta {
+        Meta::Obsoletes(set) => Some(set),
+        _ => None,
+    });
+    assert_eq!(obsoletes.len(), 6);
+}
+
+#[test]
+fn text_without_name() {
+    assert!(IetfDoc::<Ids>::from_text("just some text\n\nwithout a header\n").is_err());
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use common::{fixtures, ids, meta_ids, Ids};
+use rfc_dep_ietf::error::DocError;
+use rfc_dep_ietf::{DocSource, FixtureSource, IetfDoc, Meta, SourceFuture, SourceResponse};
+use url::Url;
+
+/* fixtures whose pages of documents citing a document can't be fetched */
+#[derive(Debug)]
+struct NoReferencedBy(FixtureSource);
+
+impl DocSource for NoReferencedBy {
+    fn get<'a>(&'a self, url: &'a Url) -> SourceFuture<'a> {
+        if url.path().ends_with("/referencedby/") {
+            return Box::pin(async move { Err(DocError::Connection { url: url.to_string(), source: None }) });
+        }
+        self.0.get(url)
+    }
+}
+
+/* fixtures whose document xml has a mismatched end tag */
+#[derive(Debug)]
+struct MalformedXml(FixtureSource);
+
+impl DocSource for MalformedXml {
+    fn get<'a>(&'a self, url: &'a Url) -> SourceFuture<'a> {
+        Box::pin(async move {
+            let resp = self.0.get(url).await?;
+            if !url.path().ends_with(".xml") {
+                return Ok(resp);
+            }
+            let xml = String::from_utf8_lossy(resp.bytes()).replace("</references>", "</reference>");
+            Ok(SourceResponse::new(resp.url().clone(), xml.into_bytes()))
+        })
+    }
+}
+
+#[test]
+fn draft_xml_summary() {
+    let doc = IetfDoc::<Ids>::from_name_with(&fixtures(), "draft-ietf-idr-example").unwrap();
+    let summary = &doc.summary;
+
+    assert_eq!(summary.id.to_string(), "draft-ietf-idr-example-03");
+    assert_eq!(summary.revision, "03");
+    assert!(!summary.is_rfc);
+    assert_eq!(summary.title, "An Example BGP Extension");
+    assert_eq!(summary.status.as_deref(), Some("Standards Track"));
+    assert_eq!(summary.stream.as_deref(), Some("IETF"));
+    assert_eq!(summary.group.as_deref(), Some("idr"));
+    assert_eq!(summary.authors, vec!["Jane Doe", "A. Smith"]);
+    assert_eq!(summary.date.as_deref(), Some("2023-07-04"));
+    let abstract_text = summary.abstract_text.as_deref().unwrap();
+    assert!(abstract_text.starts_with("This document defines an example extension of BGP-4 & its flow"));
+    assert_eq!(doc.revisions.len(), 4);
+}
+
+#[test]
+fn draft_xml_metas() {
+    let doc = IetfDoc::<Ids>::from_name_with(&fixtures(), "draft-ietf-idr-example").unwrap();
+
+    let updates = meta_ids(&doc, |meta| match meta {
+        Meta::Updates(set) => Some(set),
+        _ => None,
+    });
+    assert_eq!(updates, ids(&["rfc4271", "rfc8955"]));
+
+    // the subseries of a reference group is referenced along with its rfcs
+    let normative = meta_ids(&doc, |meta| match meta {
+        Meta::NormativeReferences(set) => Some(set),
+        _ => None,
+    });
+    assert_eq!(normative, ids(&["bcp14", "rfc2119", "rfc8174", "rfc4271", "rfc8955"]));
+
+    // references to other kinds of documents (IEEE) are ignored
+    let informative = meta_ids(&doc, |meta| match meta {
+        Meta::InformativeReferences(set) => Some(set),
+        _ => None,
+    });
+    assert_eq!(informative, ids(&["draft-ietf-idr-flowspec-v2-02", "draft-ietf-idr-bgp-model"]));
+
+    let referenced_by = meta_ids(&doc, |meta| match meta {
+        Meta::NormativelyReferencedBy(set) => Some(set),
+        _ => None,
+    });
+    assert_eq!(referenced_by, ids(&["draft-ietf-idr-example-use"]));
+}
+
+#[test]
+fn failed_referenced_by_fails_the_document() {
+    let err = IetfDoc::<Ids>::from_name_with(&NoReferencedBy(fixtures()), "draft-ietf-idr-example").unwrap_err();
+    assert!(matches!(err, DocError::Connection { .. }));
+}
+
+#[test]
+fn malformed_xml_fails_the_document() {
+    let err = IetfDoc::<Ids>::from_name_with(&MalformedXml(fixtures()), "draft-ietf-idr-example").unwrap_err();
+    assert!(matches!(err, DocError::Xml { .. }));
+}
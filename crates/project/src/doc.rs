@@ -0,0 +1,384 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+
+use rfc_dep_cache::{CacheReference, RelationalEntry, ResolvableEntry};
+use rfc_dep_ietf::error::DocError;
+use rfc_dep_ietf::{DocFormat, DocIdentifier, FetchState, IdContainer, IetfDoc, Meta};
+
+use crate::offline::Offline;
+
+#[derive(Clone, Debug, Serialize, Deserialize, Hash, PartialEq, Eq)]
+/* Type Wrapper needed because CacheReference is from rfc_dep_cache
+ * and IdContainer is from rfc_dep_doc */
+pub struct DocReference(pub CacheReference<DocIdentifier>);
+
+/* make DocReference an IdContainer to allow it to be contained in IetfDoc::Meta */
+impl IdContainer for DocReference {
+    type Holder<T> = DocReference;
+}
+
+impl From<DocIdentifier> for DocReference {
+    fn from(value: DocIdentifier) -> Self {
+        CacheReference::Unknown(value).into()
+    }
+}
+
+impl From<DocReference> for CacheReference<DocIdentifier> {
+    fn from(value: DocReference) -> Self {
+        value.0
+    }
+}
+
+impl From<CacheReference<DocIdentifier>> for DocReference {
+    fn from(value: CacheReference<DocIdentifier>) -> Self {
+        DocReference(value)
+    }
+}
+
+impl Deref for DocReference {
+    type Target = DocIdentifier;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.deref()
+    }
+}
+
+impl DocReference {
+    fn get_mut(&mut self) -> &mut CacheReference<DocIdentifier> {
+        let DocReference(ref mut cache) = self;
+        cache
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StatefulDoc {
+    // Target document
+    pub content: IetfDoc<DocReference>,
+
+    // Real State
+    pub is_read: bool,
+    pub is_selected: bool,
+    pub missing_dep_count: usize,
+    #[serde(default)]
+    pub offline: Offline,
+    // newer revision of a draft found on the datatracker, see check_revisions
+    pub latest_revision: Option<String>,
+    // when the document was queried and the validators of its pages, see refresh
+    #[serde(default)]
+    pub fetch: FetchState,
+
+    // Temporary State
+    pub to_resolve: bool,
+}
+
+impl StatefulDoc {
+    pub fn new(doc: IetfDoc<DocReference>) -> StatefulDoc {
+        let mut doc = StatefulDoc {
+            missing_dep_count: 0,
+            content: doc,
+            is_read: false,
+            is_selected: false,
+            to_resolve: false,
+            offline: Offline::default(),
+            latest_revision: None,
+            fetch: FetchState::default(),
+        };
+
+        doc.missing_dep_count = doc.get_unknown_relations_count();
+
+        doc
+    }
+
+    /* key of the document in a project, drafts are cached whatever their revision */
+    pub fn id(&self) -> DocIdentifier {
+        self.content.summary.id.unversioned()
+    }
+
+    /* newer revision of a draft than the cached one, if any */
+    pub fn newer_revision(&self) -> Option<&str> {
+        let revision = |rev: &str| rev.parse::<u32>().ok();
+        self.latest_revision
+            .as_deref()
+            .filter(|latest| revision(latest) > revision(&self.content.summary.revision))
+    }
+
+    /* replace the document by another revision of it, keeping the reading state
+     * the offline copies and the validators are dropped since they are the ones of the previous revision */
+    pub fn select_revision(&mut self, mut doc: IetfDoc<DocReference>) {
+        if doc.summary.revision != self.content.summary.revision {
+            self.offline.clear();
+            self.fetch = FetchState::default();
+        }
+        self.keep_revision_dates(&mut doc);
+        self.content = doc;
+        self.missing_dep_count = self.get_unknown_relations_count();
+    }
+
+    /* keep the dates of the revision history in another query of the document */
+    fn keep_revision_dates(&self, doc: &mut IetfDoc<DocReference>) {
+        for revision in doc.revisions.iter_mut().filter(|revision| revision.date.is_none()) {
+            revision.date = self
+                .content
+                .revisions
+                .iter()
+                .find(|known| known.revision == revision.revision)
+                .and_then(|known| known.date.clone());
+        }
+    }
+
+    /* update the document from a new query of it, keeping its reading state, offline copies and errata
+     * the queried metas replace the known ones, metas the query doesn't give (from the text, the rfc index) are kept
+     * as well as the summary fields it doesn't give */
+    pub fn refresh_from(&mut self, mut doc: IetfDoc<DocReference>, fetch: FetchState) {
+        self.keep_revision_dates(&mut doc);
+        doc.summary.complete_from(&self.content.summary);
+
+        self.content.summary = doc.summary;
+        self.content.revisions = doc.revisions;
+        for meta in doc.meta.values() {
+            self.content.meta.insert(meta.clone());
+        }
+        self.fetch = fetch;
+        self.missing_dep_count = self.get_unknown_relations_count();
+    }
+
+    /* download a copy of the document in a format, kept along the copies in other formats */
+    pub fn download_format(&mut self, format: DocFormat) -> rfc_dep_ietf::error::Result<()> {
+        if self.offline.has(format) {
+            return Ok(());
+        }
+
+        let content = self.content.download_format(format)?;
+        self.offline.insert(format, content)
+    }
+
+    /* download the document in the preferred format, or as text when it isn't published in this format
+     * returns the format downloaded */
+    pub fn download(&mut self, preferred: DocFormat) -> rfc_dep_ietf::error::Result<DocFormat> {
+        match self.download_format(preferred) {
+            Ok(()) => Ok(preferred),
+            Err(DocError::NotFound { .. }) if preferred != DocFormat::Text => {
+                self.download_format(DocFormat::Text)?;
+                Ok(DocFormat::Text)
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+impl ResolvableEntry<DocIdentifier> for StatefulDoc {
+    fn get_value(id: DocIdentifier) -> Result<Self, String> {
+        let (doc, fetch) = IetfDoc::fetch(id.to_string())?;
+        let mut state = StatefulDoc::new(doc);
+        state.fetch = fetch;
+
+        Ok(state)
+    }
+}
+
+pub fn update_missing_dep_count(doc: &mut StatefulDoc, new_deps: isize) {
+    doc.missing_dep_count = (doc.missing_dep_count as isize - new_deps) as usize;
+}
+
+impl StatefulDoc {
+    /* ids of the relations not linked to a cached document, along with the references if 'references' */
+    fn unknown_relations(&self, references: bool) -> HashSet<DocIdentifier> {
+        let mut to_update = HashSet::new();
+        let mut add_unknown = |item: &CacheReference<DocIdentifier>| {
+            match item {
+                CacheReference::Unknown(id) => {
+                    to_update.insert(id.unversioned());
+                }
+                CacheReference::Cached(_) => {}
+            };
+        };
+
+        for (_, meta) in self.content.meta.deref() {
+            match meta {
+                Meta::NormativeReferences(_) | Meta::InformativeReferences(_) if !references => {}
+                Meta::Updates(list)
+                | Meta::Obsoletes(list)
+                | Meta::UpdatedBy(list)
+                | Meta::ObsoletedBy(list)
+                | Meta::AlsoKnownAs(list)
+                | Meta::Contains(list)
+                | Meta::NormativeReferences(list)
+                | Meta::InformativeReferences(list) => {
+                    for DocReference(item) in list {
+                        add_unknown(item);
+                    }
+                }
+                Meta::Replaces(DocReference(item))
+                | Meta::ReplacedBy(DocReference(item))
+                | Meta::Was(DocReference(item)) => {
+                    add_unknown(item);
+                }
+                // citing documents are only linked when cached, never queried
+                Meta::NormativelyReferencedBy(_) | Meta::InformativelyReferencedBy(_) => {}
+            }
+        }
+
+        to_update
+    }
+}
+
+// Implement resolve dependency algorithms when value is IetfDoc
+impl RelationalEntry<DocIdentifier> for StatefulDoc {
+    fn get_unknown_relations(&self) -> HashSet<DocIdentifier> {
+        self.unknown_relations(true)
+    }
+
+    fn get_unknown_relations_without_references(&self) -> HashSet<DocIdentifier> {
+        self.unknown_relations(false)
+    }
+
+    fn update_unknown_references(&mut self, is_known: impl Fn(&DocIdentifier) -> bool) -> isize {
+        let mut change = 0;
+
+        // returns the change in known references
+        let update_cache_ref = |cache_ref: &mut CacheReference<DocIdentifier>| -> isize {
+            // drafts are linked whatever the revision they are referenced with
+            let (new_ref, delta) = match cache_ref {
+                CacheReference::Unknown(ref r) if is_known(&r.unversioned()) => {
+                    (CacheReference::Cached(r.unversioned()), 1)
+                }
+                CacheReference::Cached(ref r) if !is_known(&r.unversioned()) => {
+                    (CacheReference::Unknown(r.clone()), -1)
+                }
+                CacheReference::Cached(ref r) if r.revision().is_some() => {
+                    (CacheReference::Cached(r.unversioned()), 0)
+                }
+                CacheReference::Unknown(_) | CacheReference::Cached(_) => return 0,
+            };
+            *cache_ref = new_ref;
+            delta
+        };
+
+        let update_set = |set: &mut HashSet<DocReference>| -> isize {
+            let mut delta = 0;
+            let mut values = mem::take(set);
+            let _ = mem::replace(set, values.drain().map(|mut x| {
+                delta += update_cache_ref(x.get_mut());
+                x
+            }).collect());
+            delta
+        };
+
+        for (_, meta) in self.content.meta.deref_mut() {
+            match meta {
+                Meta::Updates(set)
+                | Meta::Obsoletes(set)
+                | Meta::UpdatedBy(set)
+                | Meta::ObsoletedBy(set)
+                | Meta::AlsoKnownAs(set)
+                | Meta::Contains(set)
+                | Meta::NormativeReferences(set)
+                | Meta::InformativeReferences(set) => {
+                    change += update_set(set);
+                }
+                Meta::Replaces(DocReference(ref mut cache_ref))
+                | Meta::ReplacedBy(DocReference(ref mut cache_ref))
+                | Meta::Was(DocReference(ref mut cache_ref)) => {
+                    change += update_cache_ref(cache_ref);
+                }
+                // linked for display but not counted as missing dependencies
+                Meta::NormativelyReferencedBy(set) | Meta::InformativelyReferencedBy(set) => {
+                    update_set(set);
+                }
+            }
+        }
+
+        change
+    }
+
+    fn get_unknown_relations_count(&self) -> usize {
+        let mut missing = 0;
+
+        let count_meta = |cache_ref: &CacheReference<_>| match cache_ref {
+            CacheReference::Unknown(_) => 1,
+            CacheReference::Cached(_) => 0,
+        };
+
+        for (_, meta) in self.content.meta.deref() {
+            match meta {
+                Meta::Updates(set)
+                | Meta::Obsoletes(set)
+                | Meta::UpdatedBy(set)
+                | Meta::ObsoletedBy(set)
+                | Meta::AlsoKnownAs(set)
+                | Meta::Contains(set)
+                | Meta::NormativeReferences(set)
+                | Meta::InformativeReferences(set) => {
+                    for DocReference(item) in set {
+                        missing += count_meta(item);
+                    }
+                }
+                Meta::Replaces(DocReference(item))
+                | Meta::ReplacedBy(DocReference(item))
+                | Meta::Was(DocReference(item)) => {
+                    missing += count_meta(item);
+                }
+                Meta::NormativelyReferencedBy(_) | Meta::InformativelyReferencedBy(_) => {}
+            }
+        }
+
+        missing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{doc, references};
+    use rfc_dep_ietf::{Erratum, ErratumStatus, ErratumType, Validators};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn refresh_keeps_the_state() {
+        let mut state = doc(
+            "rfc9000",
+            vec![Meta::AlsoKnownAs(references(&["std99"])), Meta::Updates(references(&["rfc8000"]))],
+        );
+        state.is_read = true;
+        state.offline.insert_text(DocFormat::Text, "text".to_string());
+        state.content.summary.pages = Some(20);
+        state.content.errata = Some(vec![Erratum {
+            id: 1,
+            kind: ErratumType::Technical,
+            status: ErratumStatus::Verified,
+            section: None,
+            original: None,
+            corrected: None,
+        }]);
+
+        let mut queried = doc("rfc9000", vec![Meta::Updates(references(&["rfc8000", "rfc8001"]))]).content;
+        queried.summary.title = "New title".to_string();
+        let validators = Validators {
+            etag: Some("\"1\"".to_string()),
+            last_modified: None,
+        };
+        let fetch = FetchState {
+            fetched_at: Some(1),
+            validators: BTreeMap::from([("https://datatracker.ietf.org/doc/rfc9000".to_string(), validators)]),
+        };
+        state.refresh_from(queried, fetch.clone());
+
+        assert!(state.is_read);
+        assert!(state.offline.has(DocFormat::Text));
+        assert_eq!(state.content.errata.as_ref().map(Vec::len), Some(1));
+        assert_eq!(state.fetch, fetch);
+        // summary fields the query doesn't give are kept
+        assert_eq!(state.content.summary.title, "New title");
+        assert_eq!(state.content.summary.pages, Some(20));
+
+        // queried metas replace the known ones, the others are kept
+        let updates = state.content.meta.values().find_map(|meta| match meta {
+            Meta::Updates(set) => Some(set.clone()),
+            _ => None,
+        });
+        assert_eq!(updates, Some(references(&["rfc8000", "rfc8001"])));
+        assert!(state.content.meta.values().any(|meta| matches!(meta, Meta::AlsoKnownAs(_))));
+    }
+}
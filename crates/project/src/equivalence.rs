@@ -0,0 +1,338 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::mem;
+use std::ops::Deref;
+
+use rfc_dep_cache::{CacheReference, RelationalEntry};
+use rfc_dep_ietf::{DocIdentifier, Meta, MetaMap};
+
+use crate::doc::DocReference;
+use crate::project::DocCache;
+
+/* Groups of cached documents which are the same work at different stages: an rfc, the drafts it was
+ * published from and the drafts they replace (Was, Replaces), and the subseries made of this rfc only
+ * each group is shown as one logical document, its representative: the rfc, or the latest draft */
+#[derive(Debug, Clone, Default)]
+pub struct Equivalence {
+    // representative of every document of a group
+    representatives: HashMap<DocIdentifier, DocIdentifier>,
+    // documents of each group by representative, representative first
+    groups: HashMap<DocIdentifier, Vec<DocIdentifier>>,
+}
+
+/* cached documents equivalent to a document, by the relations of this document only */
+fn equivalent_links(cache: &DocCache, id: &DocIdentifier) -> Vec<DocIdentifier> {
+    let Some(state) = cache.get(id) else {
+        return Vec::new();
+    };
+
+    let mut links = Vec::new();
+    for (_, meta) in state.content.meta.deref() {
+        match meta {
+            Meta::Was(other) | Meta::Replaces(other) | Meta::ReplacedBy(other) => links.push(other.unversioned()),
+            // a subseries of several rfcs (bcp14) isn't any of them
+            Meta::Contains(members) if members.len() == 1 => {
+                links.extend(members.iter().map(|member| member.unversioned()))
+            }
+            _ => {}
+        }
+    }
+    links.retain(|other| other != id && cache.has_id(other));
+
+    links
+}
+
+/* the rfc (latest one) of a group, or its draft which isn't replaced by another one of the group */
+fn representative(cache: &DocCache, group: &[DocIdentifier]) -> DocIdentifier {
+    if let Some(rfc) = group.iter().filter(|id| id.is_rfc()).max() {
+        return rfc.clone();
+    }
+
+    let replaced = |id: &DocIdentifier| {
+        cache.get(id).is_some_and(|state| {
+            state.content.meta.values().any(|meta| {
+                matches!(meta, Meta::ReplacedBy(other) if group.contains(&other.unversioned()))
+            })
+        })
+    };
+    group
+        .iter()
+        .find(|id| id.is_draft() && !replaced(id))
+        .unwrap_or(&group[0])
+        .clone()
+}
+
+/* relation of a group to other documents, references to documents of the group are dropped
+ * references to cached documents point to their representative */
+fn map_meta(meta: &Meta<DocReference>, map: impl Fn(&DocReference) -> Option<DocReference>) -> Option<Meta<DocReference>> {
+    let map_set = |set: &HashSet<DocReference>| -> Option<HashSet<DocReference>> {
+        Some(set.iter().filter_map(&map).collect::<HashSet<_>>()).filter(|set| !set.is_empty())
+    };
+
+    Some(match meta {
+        Meta::Updates(set) => Meta::Updates(map_set(set)?),
+        Meta::UpdatedBy(set) => Meta::UpdatedBy(map_set(set)?),
+        Meta::Obsoletes(set) => Meta::Obsoletes(map_set(set)?),
+        Meta::ObsoletedBy(set) => Meta::ObsoletedBy(map_set(set)?),
+        Meta::AlsoKnownAs(set) => Meta::AlsoKnownAs(map_set(set)?),
+        Meta::Contains(set) => Meta::Contains(map_set(set)?),
+        Meta::NormativeReferences(set) => Meta::NormativeReferences(map_set(set)?),
+        Meta::InformativeReferences(set) => Meta::InformativeReferences(map_set(set)?),
+        Meta::NormativelyReferencedBy(set) => Meta::NormativelyReferencedBy(map_set(set)?),
+        Meta::InformativelyReferencedBy(set) => Meta::InformativelyReferencedBy(map_set(set)?),
+        Meta::Replaces(item) => Meta::Replaces(map(item)?),
+        Meta::ReplacedBy(item) => Meta::ReplacedBy(map(item)?),
+        Meta::Was(item) => Meta::Was(map(item)?),
+    })
+}
+
+/* add a relation to the merged ones, single relations (Was, Replaces...) keep the first document */
+fn merge_meta(merged: &mut MetaMap<DocReference>, meta: Meta<DocReference>) {
+    let known = merged
+        .values_mut()
+        .find(|known| mem::discriminant(*known) == mem::discriminant(&meta));
+
+    match (known, meta) {
+        (Some(Meta::Updates(set)), Meta::Updates(other))
+        | (Some(Meta::UpdatedBy(set)), Meta::UpdatedBy(other))
+        | (Some(Meta::Obsoletes(set)), Meta::Obsoletes(other))
+        | (Some(Meta::ObsoletedBy(set)), Meta::ObsoletedBy(other))
+        | (Some(Meta::AlsoKnownAs(set)), Meta::AlsoKnownAs(other))
+        | (Some(Meta::Contains(set)), Meta::Contains(other))
+        | (Some(Meta::NormativeReferences(set)), Meta::NormativeReferences(other))
+        | (Some(Meta::InformativeReferences(set)), Meta::InformativeReferences(other))
+        | (Some(Meta::NormativelyReferencedBy(set)), Meta::NormativelyReferencedBy(other))
+        | (Some(Meta::InformativelyReferencedBy(set)), Meta::InformativelyReferencedBy(other)) => set.extend(other),
+        (Some(_), _) => {}
+        (None, meta) => {
+            merged.insert(meta);
+        }
+    }
+}
+
+impl Equivalence {
+    pub fn new(cache: &DocCache) -> Equivalence {
+        // relations are followed both ways, a draft may not know the rfc it became
+        let mut links: HashMap<DocIdentifier, Vec<DocIdentifier>> = HashMap::new();
+        for id in cache.keys() {
+            for other in equivalent_links(cache, id) {
+                links.entry(id.clone()).or_default().push(other.clone());
+                links.entry(other).or_default().push(id.clone());
+            }
+        }
+
+        let mut equivalence = Equivalence::default();
+        for id in cache.keys() {
+            if equivalence.representatives.contains_key(id) || !links.contains_key(id) {
+                continue;
+            }
+
+            let mut group = vec![id.clone()];
+            let mut visited = HashSet::from([id.clone()]);
+            let mut queue = VecDeque::from([id.clone()]);
+            while let Some(current) = queue.pop_front() {
+                for other in links.get(&current).into_iter().flatten() {
+                    if visited.insert(other.clone()) {
+                        group.push(other.clone());
+                        queue.push_back(other.clone());
+                    }
+                }
+            }
+
+            let head = representative(cache, &group);
+            group.sort_by_key(|member| *member != head);
+            for member in &group {
+                equivalence.representatives.insert(member.clone(), head.clone());
+            }
+            equivalence.groups.insert(head, group);
+        }
+
+        equivalence
+    }
+
+    /* the document shown for a document, itself when it isn't equivalent to any other */
+    pub fn representative(&self, id: &DocIdentifier) -> DocIdentifier {
+        self.representatives.get(id).unwrap_or(id).clone()
+    }
+
+    pub fn is_representative(&self, id: &DocIdentifier) -> bool {
+        self.representatives.get(id).is_none_or(|head| head == id)
+    }
+
+    /* documents of the group of a document, representative first */
+    pub fn members(&self, id: &DocIdentifier) -> Vec<DocIdentifier> {
+        match self.groups.get(&self.representative(id)) {
+            Some(group) => group.clone(),
+            None => vec![id.clone()],
+        }
+    }
+
+    /* groups of several documents by representative */
+    pub fn groups(&self) -> &HashMap<DocIdentifier, Vec<DocIdentifier>> {
+        &self.groups
+    }
+
+    /* relations of every document of the group of a document, as those of the logical document */
+    pub fn merged_meta(&self, cache: &DocCache, id: &DocIdentifier) -> MetaMap<DocReference> {
+        let head = self.representative(id);
+        let map = |DocReference(reference): &DocReference| match reference {
+            CacheReference::Cached(other) => {
+                let other = self.representative(other);
+                (other != head).then_some(DocReference(CacheReference::Cached(other)))
+            }
+            CacheReference::Unknown(other) => Some(DocReference(CacheReference::Unknown(other.clone()))),
+        };
+
+        let mut merged = MetaMap::default();
+        for member in self.members(id) {
+            let Some(state) = cache.get(&member) else {
+                continue;
+            };
+            for (_, meta) in state.content.meta.deref() {
+                if let Some(meta) = map_meta(meta, map) {
+                    merge_meta(&mut merged, meta);
+                }
+            }
+        }
+
+        merged
+    }
+
+    /* unknown relations of the group of a document, each document counted once */
+    pub fn missing_dep_count(&self, cache: &DocCache, id: &DocIdentifier) -> usize {
+        let mut unknown = HashSet::new();
+        for member in self.members(id) {
+            if let Some(state) = cache.get(&member) {
+                unknown.extend(state.get_unknown_relations());
+            }
+        }
+
+        unknown.len()
+    }
+
+    /* a group is read when any of its documents is */
+    pub fn is_read(&self, cache: &DocCache, id: &DocIdentifier) -> bool {
+        self.members(id)
+            .iter()
+            .any(|member| cache.get(member).is_some_and(|state| state.is_read))
+    }
+
+    /* set the reading state of every document of the group of a document */
+    pub fn set_read(&self, cache: &mut DocCache, id: &DocIdentifier, is_read: bool) {
+        for member in self.members(id) {
+            if let Some(state) = cache.get_mut(&member) {
+                state.is_read = is_read;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::update_relations;
+    use crate::testing::{id, ids, project, reference, references};
+
+    /* rfc9000 was draft-a, replaced by draft-b which became rfc9100, rfc9100 is std99 */
+    fn published() -> DocCache {
+        let mut cache = project(vec![
+            ("draft-a-04", vec![Meta::ReplacedBy(reference("draft-b")), Meta::Updates(references(&["rfc8000"]))]),
+            ("draft-b-02", vec![Meta::Replaces(reference("draft-a"))]),
+            (
+                "rfc9000",
+                vec![Meta::Was(reference("draft-a")), Meta::NormativeReferences(references(&["rfc2119"]))],
+            ),
+            (
+                "rfc9100",
+                vec![
+                    Meta::Was(reference("draft-b")),
+                    Meta::NormativeReferences(references(&["rfc8174", "draft-c"])),
+                ],
+            ),
+            ("std99", vec![Meta::Contains(references(&["rfc9100"]))]),
+            ("bcp14", vec![Meta::Contains(references(&["rfc2119", "rfc8174"]))]),
+            ("rfc2119", vec![]),
+            ("rfc8174", vec![]),
+            ("draft-c-00", vec![Meta::InformativeReferences(references(&["draft-a"]))]),
+        ]);
+        update_relations(&mut cache, true);
+
+        cache
+    }
+
+    fn sorted(mut ids: Vec<DocIdentifier>) -> Vec<DocIdentifier> {
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn latest_rfc_represents_its_group() {
+        let cache = published();
+        let equivalence = Equivalence::new(&cache);
+
+        let head = id("rfc9100");
+        for member in ["draft-a", "draft-b", "rfc9000", "rfc9100", "std99"] {
+            assert_eq!(equivalence.representative(&id(member)), head, "representative of {}", member);
+        }
+        let members = equivalence.members(&id("draft-a"));
+        assert_eq!(members[0], head);
+        assert_eq!(sorted(members), sorted(ids(&["draft-a", "draft-b", "rfc9000", "rfc9100", "std99"])));
+        assert!(!equivalence.is_representative(&id("rfc9000")));
+
+        // a subseries of several rfcs isn't any of them
+        for other in ["bcp14", "rfc2119", "rfc8174", "draft-c"] {
+            assert!(equivalence.is_representative(&id(other)));
+            assert_eq!(equivalence.members(&id(other)), ids(&[other]));
+        }
+        assert_eq!(equivalence.groups().len(), 1);
+    }
+
+    #[test]
+    fn draft_not_replaced_represents_its_group() {
+        let cache = project(vec![
+            ("draft-a-04", vec![Meta::ReplacedBy(reference("draft-b"))]),
+            ("draft-b-02", vec![Meta::ReplacedBy(reference("draft-c"))]),
+            // draft-c doesn't know the drafts it replaces
+            ("draft-c-00", vec![]),
+        ]);
+        let equivalence = Equivalence::new(&cache);
+
+        assert_eq!(equivalence.representative(&id("draft-a")), id("draft-c"));
+        assert_eq!(equivalence.representative(&id("draft-b")), id("draft-c"));
+        assert_eq!(equivalence.members(&id("draft-c"))[0], id("draft-c"));
+    }
+
+    #[test]
+    fn merged_relations() {
+        let cache = published();
+        let equivalence = Equivalence::new(&cache);
+        let merged = equivalence.merged_meta(&cache, &id("rfc9000"));
+
+        let set = |pick: fn(&Meta<DocReference>) -> Option<&HashSet<DocReference>>| -> Vec<DocIdentifier> {
+            sorted(merged.values().filter_map(pick).flatten().map(|reference| reference.deref().clone()).collect())
+        };
+
+        // relations inside the group are dropped
+        assert!(!merged
+            .values()
+            .any(|meta| matches!(meta, Meta::Was(_) | Meta::Replaces(_) | Meta::ReplacedBy(_) | Meta::Contains(_))));
+        // the others are merged
+        let normative = set(|meta| match meta {
+            Meta::NormativeReferences(set) => Some(set),
+            _ => None,
+        });
+        assert_eq!(normative, sorted(ids(&["draft-c", "rfc2119", "rfc8174"])));
+        let updates = set(|meta| match meta {
+            Meta::Updates(set) => Some(set),
+            _ => None,
+        });
+        assert_eq!(updates, ids(&["rfc8000"]));
+
+        // references to a member of the group point to its representative
+        let merged = equivalence.merged_meta(&cache, &id("draft-c"));
+        let cited = merged.values().find_map(|meta| match meta {
+            Meta::InformativeReferences(set) => Some(set.clone()),
+            _ => None,
+        });
+        assert_eq!(cited, Some(HashSet::from([DocReference(CacheReference::Cached(id("rfc9100")))])));
+    }
+}
@@ -0,0 +1,65 @@
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use rfc_dep_ietf::error::DocError;
+use ProjectError::*;
+
+pub type Result<T> = std::result::Result<T, ProjectError>;
+pub enum ProjectError {
+    Io(String),
+    Json(String),
+    Doc(String),
+}
+
+impl ProjectError {
+    fn name(&self) -> &'static str {
+        match self {
+            Io(_) => "IoError",
+            Json(_) => "JsonError",
+            Doc(_) => "DocError",
+        }
+    }
+
+    fn description(&self) -> &str {
+        match self {
+            Io(s) | Json(s) | Doc(s) => s.as_str(),
+        }
+    }
+}
+
+impl From<ProjectError> for String {
+    fn from(value: ProjectError) -> Self {
+        value.to_string()
+    }
+}
+
+impl Debug for ProjectError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{{{}}}", self.name(), self.description())
+    }
+}
+
+impl Display for ProjectError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{{{}}}", self.name(), self.description())
+    }
+}
+
+impl Error for ProjectError {}
+
+impl From<std::io::Error> for ProjectError {
+    fn from(value: std::io::Error) -> Self {
+        Io(value.to_string())
+    }
+}
+
+impl From<serde_json::Error> for ProjectError {
+    fn from(value: serde_json::Error) -> Self {
+        Json(value.to_string())
+    }
+}
+
+impl From<DocError> for ProjectError {
+    fn from(value: DocError) -> Self {
+        Doc(value.to_string())
+    }
+}
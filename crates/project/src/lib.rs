@@ -0,0 +1,14 @@
+mod doc;
+mod equivalence;
+pub mod error;
+mod offline;
+mod project;
+mod supersede;
+#[cfg(test)]
+mod testing;
+
+pub use doc::*;
+pub use equivalence::*;
+pub use offline::*;
+pub use project::*;
+pub use supersede::*;
@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use rfc_dep_ietf::error::DocError;
+use rfc_dep_ietf::DocFormat;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/* Downloaded copies of a document by format, pdf copies are kept base64 encoded
+ * so that projects stay plain JSON */
+#[derive(Clone, Debug, Default)]
+pub struct Offline {
+    copies: BTreeMap<DocFormat, String>,
+}
+
+/* projects saved with a single text copy ("offline": "...") are loaded as its txt copy */
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OfflineRepr {
+    Text(String),
+    Copies(BTreeMap<DocFormat, String>),
+}
+
+impl Serialize for Offline {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.copies.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Offline {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let copies = match Option::<OfflineRepr>::deserialize(deserializer)? {
+            None => BTreeMap::new(),
+            Some(OfflineRepr::Text(text)) => BTreeMap::from([(DocFormat::Text, text)]),
+            Some(OfflineRepr::Copies(copies)) => copies,
+        };
+
+        Ok(Offline { copies })
+    }
+}
+
+impl Offline {
+    pub fn is_empty(&self) -> bool {
+        self.copies.is_empty()
+    }
+
+    pub fn has(&self, format: DocFormat) -> bool {
+        self.copies.contains_key(&format)
+    }
+
+    /* formats downloaded, in the order of DocFormat */
+    pub fn formats(&self) -> impl Iterator<Item = DocFormat> + '_ {
+        self.copies.keys().copied()
+    }
+
+    /* copy in a text format (txt, html, xml) */
+    pub fn text(&self, format: DocFormat) -> Option<&str> {
+        self.copies
+            .get(&format)
+            .filter(|_| format.is_text())
+            .map(String::as_str)
+    }
+
+    /* content of a copy as downloaded */
+    pub fn bytes(&self, format: DocFormat) -> Option<Vec<u8>> {
+        let copy = self.copies.get(&format)?;
+        if format.is_text() {
+            Some(copy.as_bytes().to_vec())
+        } else {
+            STANDARD.decode(copy).ok()
+        }
+    }
+
+    /* keep a copy, replacing the previous one of its format
+     * text formats must be valid utf-8 */
+    pub fn insert(&mut self, format: DocFormat, content: Vec<u8>) -> rfc_dep_ietf::error::Result<()> {
+        let copy = if format.is_text() {
+            String::from_utf8(content)
+                .map_err(|err| DocError::Query(format!("{} copy is not valid utf-8: {}", format, err)))?
+        } else {
+            STANDARD.encode(content)
+        };
+        self.copies.insert(format, copy);
+
+        Ok(())
+    }
+
+    pub fn insert_text(&mut self, format: DocFormat, text: String) {
+        if format.is_text() {
+            self.copies.insert(format, text);
+        }
+    }
+
+    pub fn remove(&mut self, format: DocFormat) {
+        self.copies.remove(&format);
+    }
+
+    pub fn clear(&mut self) {
+        self.copies.clear();
+    }
+
+    /* format to show a document in: the preferred one when downloaded and readable as text
+     * otherwise txt, then xml and html (their source is shown), pdf only if there is nothing else */
+    pub fn best_format(&self, preferred: DocFormat) -> Option<DocFormat> {
+        let order = [preferred, DocFormat::Text, DocFormat::Xml, DocFormat::Html];
+        order
+            .into_iter()
+            .filter(|format| format.is_text())
+            .find(|format| self.has(*format))
+            .or_else(|| self.formats().next())
+    }
+}
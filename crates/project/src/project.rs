@@ -0,0 +1,324 @@
+use rayon::prelude::*;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::ops::Deref;
+use std::path::Path;
+
+use rfc_dep_cache::{Cache, CacheReference, RelationalEntry, ResolveParams, ResolveTarget};
+use rfc_dep_ietf::error::DocError;
+use rfc_dep_ietf::{
+    load_rfc_index, DocFormat, DocIdentifier, ErrataMap, FetchState, IetfDoc, Meta, Refresh, Summary,
+};
+
+use crate::doc::{update_missing_dep_count, DocReference, StatefulDoc};
+use crate::error::{ProjectError, Result};
+
+/* A project is a cache of documents along with their reading state */
+pub type DocCache = Cache<DocIdentifier, StatefulDoc>;
+
+/* read a project from a json file */
+pub fn load_project(path: impl AsRef<Path>) -> Result<DocCache> {
+    let file = File::open(path)?;
+    let mut cache: DocCache = serde_json::from_reader(BufReader::new(file))?;
+    merge_revisions(&mut cache);
+    update_relations(&mut cache, true);
+
+    Ok(cache)
+}
+
+/* write a project to a json file */
+pub fn save_project(cache: &DocCache, path: impl AsRef<Path>) -> Result<()> {
+    let file = File::create(path)?;
+    serde_json::to_writer_pretty(BufWriter::new(file), cache)?;
+
+    Ok(())
+}
+
+/* key the drafts by their name without revision (projects saved with revisioned keys)
+ * the latest revision is kept when a draft is cached several times */
+fn merge_revisions(cache: &mut DocCache) {
+    let revision = |state: &StatefulDoc| state.content.summary.revision.parse::<u32>().ok();
+    let versioned: Vec<DocIdentifier> = cache.keys().filter(|id| id.revision().is_some()).cloned().collect();
+
+    for id in versioned {
+        let Some(state) = cache.remove(&id) else {
+            continue;
+        };
+        let key = id.unversioned();
+        match cache.remove(&key) {
+            Some(other) if revision(&other) >= revision(&state) => cache.cache(key, other),
+            _ => cache.cache(key, state),
+        };
+    }
+}
+
+/* consumes another project and inserts its documents in the current one */
+pub fn merge_projects(cache: &mut DocCache, other: DocCache) {
+    cache.merge_with(other);
+    update_relations(cache, false);
+}
+
+/* link relations to cached documents and update the missing dependency counts
+ * recompute the counts from scratch instead of updating them incrementally if 'recompute' */
+pub fn update_relations(cache: &mut DocCache, recompute: bool) {
+    link_subseries(cache);
+    cache.update_relations(
+        |_| false,
+        |_, doc, change| {
+            if recompute {
+                doc.missing_dep_count = doc.get_unknown_relations_count();
+            } else {
+                update_missing_dep_count(doc, change)
+            }
+        },
+    )
+}
+
+/* link the cached rfcs of the cached subseries back to them (AlsoKnownAs), when not known yet
+ * an rfc can be part of several subseries
+ * the subseries is cached so the reference is known and doesn't change the missing dependency count */
+fn link_subseries(cache: &mut DocCache) {
+    let mut members = Vec::new();
+    for (id, state) in &*cache {
+        for (_, meta) in state.content.meta.deref() {
+            if let Meta::Contains(list) = meta {
+                members.extend(list.iter().map(|member| (member.deref().clone(), id.clone())));
+            }
+        }
+    }
+
+    for (member, subseries) in members {
+        let Some(state) = cache.get_mut(&member) else {
+            continue;
+        };
+        let reference = DocReference(CacheReference::Cached(subseries));
+        let meta = &mut state.content.meta;
+        match meta.values_mut().find(|meta| matches!(meta, Meta::AlsoKnownAs(_))) {
+            // skip a subseries already known, linked or not
+            Some(Meta::AlsoKnownAs(set)) => {
+                if !set.iter().any(|known| known.deref() == reference.deref()) {
+                    set.insert(reference);
+                }
+            }
+            _ => {
+                meta.insert(Meta::AlsoKnownAs(HashSet::from([reference])));
+            }
+        }
+    }
+}
+
+/* query a document from its exact name and put it in cache, returns its id */
+pub fn import_name(cache: &mut DocCache, name: &str) -> Result<DocIdentifier> {
+    let (doc, fetch) = IetfDoc::fetch(name)?;
+    let id = doc.summary.id.unversioned();
+    let mut state = StatefulDoc::new(doc);
+    state.fetch = fetch;
+    cache.cache(id.clone(), state);
+    update_relations(cache, false);
+
+    Ok(id)
+}
+
+/* query the documents of lookup results in parallel and put them in cache
+ * returns the errors of the documents which could not be queried */
+pub fn import_summaries(cache: &mut DocCache, mut summaries: Vec<Summary>) -> Vec<DocError> {
+    let (docs, errors): (Vec<_>, Vec<_>) = summaries
+        .par_drain(..)
+        .map(IetfDoc::<DocReference>::from_summary)
+        .partition(|doc| doc.is_ok());
+
+    for doc in docs.into_iter().flatten() {
+        cache.cache(doc.summary.id.unversioned(), StatefulDoc::new(doc));
+    }
+    update_relations(cache, false);
+
+    errors.into_iter().filter_map(|doc| doc.err()).collect()
+}
+
+/* put every rfc of a local rfc-index.xml in cache without querying them
+ * documents already in cache are kept as they are, returns the number of documents added */
+pub fn import_rfc_index(cache: &mut DocCache, path: impl AsRef<Path>) -> Result<usize> {
+    let docs = load_rfc_index::<DocReference>(path)?;
+
+    let mut added = 0;
+    for doc in docs {
+        if !cache.has_id(&doc.summary.id) {
+            cache.cache(doc.summary.id.unversioned(), StatefulDoc::new(doc));
+            added += 1;
+        }
+    }
+    update_relations(cache, true);
+
+    Ok(added)
+}
+
+/* put a document built from its plain text only in cache, keeping the text as offline txt copy
+ * returns its id, a document already in cache is completed using the text instead */
+pub fn import_text(cache: &mut DocCache, text: String) -> Result<DocIdentifier> {
+    let doc = IetfDoc::<DocReference>::from_text(&text)?;
+    let id = doc.summary.id.unversioned();
+
+    let mut state = match cache.remove(&id) {
+        Some(mut state) => {
+            state.content.update_from_text(&text);
+            state
+        }
+        None => StatefulDoc::new(doc),
+    };
+    if !state.offline.has(DocFormat::Text) {
+        state.offline.insert_text(DocFormat::Text, text);
+    }
+    cache.cache(id.clone(), state);
+    update_relations(cache, true);
+
+    Ok(id)
+}
+
+/* complete the summaries and metas of the documents from their downloaded text
+ * returns the number of documents changed */
+pub fn repair_from_texts(cache: &mut DocCache) -> usize {
+    let mut repaired = 0;
+    for (_, state) in &mut *cache {
+        if let Some(text) = state.offline.text(DocFormat::Text) {
+            if state.content.update_from_text(text) > 0 {
+                repaired += 1;
+            }
+        }
+    }
+    update_relations(cache, true);
+
+    repaired
+}
+
+/* query the latest revision of every cached draft, stored in StatefulDoc::latest_revision
+ * returns the errors of the drafts which could not be queried */
+pub fn check_revisions(cache: &mut DocCache) -> Vec<DocError> {
+    let drafts: Vec<DocIdentifier> = cache.keys().filter(|id| id.is_draft()).cloned().collect();
+    let (revisions, errors): (Vec<_>, Vec<_>) = drafts
+        .par_iter()
+        .map(|id| IetfDoc::<DocReference>::latest_revision(id).map(|revision| (id, revision)))
+        .partition(|revision| revision.is_ok());
+
+    for (id, revision) in revisions.into_iter().flatten() {
+        if let Some(state) = cache.get_mut(id) {
+            state.latest_revision = revision;
+        }
+    }
+
+    errors.into_iter().filter_map(|revision| revision.err()).collect()
+}
+
+/* download the documents in the preferred format (or as text when they aren't published in it)
+ * subseries are skipped, their rfcs are the documents
+ * returns the errors of the documents which could not be downloaded */
+pub fn download_documents(cache: &mut DocCache, ids: &[DocIdentifier], preferred: DocFormat) -> Vec<DocError> {
+    cache
+        .into_iter()
+        .par_bridge()
+        .filter(|(id, _)| !id.is_subseries() && ids.contains(id))
+        .filter_map(|(_, state)| state.download(preferred).err())
+        .collect()
+}
+
+/* query the documents again when the pages they were built from changed (see IetfDoc::refresh)
+ * their reading state and offline copies are kept, subseries are refreshed too (their rfcs may change)
+ * returns the documents changed and the errors of the documents which could not be queried */
+pub fn refresh_documents(cache: &mut DocCache, ids: &[DocIdentifier]) -> (Vec<DocIdentifier>, Vec<DocError>) {
+    let queries: Vec<(DocIdentifier, DocIdentifier, FetchState)> = cache
+        .into_iter()
+        .filter(|(id, _)| ids.contains(id))
+        .map(|(id, state)| (id.clone(), state.content.summary.id.clone(), state.fetch.clone()))
+        .collect();
+
+    let (refreshes, errors): (Vec<_>, Vec<_>) = queries
+        .par_iter()
+        .map(|(key, id, fetch)| IetfDoc::<DocReference>::refresh(id, fetch).map(|refresh| (key, refresh)))
+        .partition(|refresh| refresh.is_ok());
+
+    let mut changed = Vec::new();
+    for (key, refresh) in refreshes.into_iter().flatten() {
+        let Some(state) = cache.get_mut(key) else {
+            continue;
+        };
+        match refresh {
+            Refresh::Unchanged(fetch) => state.fetch = fetch,
+            Refresh::Changed(doc, fetch) => {
+                state.refresh_from(*doc, fetch);
+                changed.push(key.clone());
+            }
+        }
+    }
+    update_relations(cache, true);
+
+    (changed, errors.into_iter().filter_map(|refresh| refresh.err()).collect())
+}
+
+/* drafts of the project having a newer revision than the cached one (see check_revisions) */
+pub fn outdated_drafts(cache: &DocCache) -> Vec<DocIdentifier> {
+    cache
+        .into_iter()
+        .filter(|(_, state)| state.newer_revision().is_some())
+        .map(|(id, _)| id.clone())
+        .collect()
+}
+
+/* query a revision of a cached draft (the latest one if the id has no revision) and select it
+ * keeps the reading state of the document, returns the id of the selected revision */
+pub fn select_revision(cache: &mut DocCache, id: &DocIdentifier) -> Result<DocIdentifier> {
+    let key = id.unversioned();
+    if !key.is_draft() || !cache.has_id(&key) {
+        return Err(ProjectError::Doc(format!("{} is not a draft of the project", id)));
+    }
+
+    let (doc, fetch) = IetfDoc::fetch(id.to_string())?;
+    let selected = doc.summary.id.clone();
+    if let Some(state) = cache.get_mut(&key) {
+        state.select_revision(doc);
+        state.fetch = fetch;
+    }
+    update_relations(cache, true);
+
+    Ok(selected)
+}
+
+/* query the revision history of a cached draft, with the date of each revision
+ * returns the number of revisions */
+pub fn update_revisions(cache: &mut DocCache, id: &DocIdentifier) -> Result<usize> {
+    let key = id.unversioned();
+    if !key.is_draft() || !cache.has_id(&key) {
+        return Err(ProjectError::Doc(format!("{} is not a draft of the project", id)));
+    }
+
+    let mut revisions = IetfDoc::<DocReference>::revision_history(&key)?;
+    let Some(state) = cache.get_mut(&key) else {
+        return Ok(0);
+    };
+
+    // keep the revisions of the page of the draft missing from the history
+    for revision in state.content.revisions.drain(..) {
+        if !revisions.iter().any(|known| known.revision == revision.revision) {
+            revisions.push(revision);
+        }
+    }
+    revisions.sort_by_key(|revision| revision.revision.parse::<u32>().ok());
+    state.content.revisions = revisions;
+
+    Ok(state.content.revisions.len())
+}
+
+/* attach their errata to the rfcs of the project (see rfc_dep_ietf::fetch_errata, load_errata)
+ * returns the number of errata attached */
+pub fn import_errata(cache: &mut DocCache, errata: &ErrataMap) -> usize {
+    cache
+        .values_mut()
+        .filter(|state| state.content.summary.is_rfc)
+        .map(|state| state.content.update_errata(errata))
+        .sum()
+}
+
+/* resolve dependencies of the target documents, updating missing dependency counts */
+pub fn resolve(cache: &mut DocCache, target: ResolveTarget<DocIdentifier>, params: ResolveParams) {
+    cache.resolve_dependencies(target, params, update_missing_dep_count);
+    update_relations(cache, true);
+}
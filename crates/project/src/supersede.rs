@@ -0,0 +1,178 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::Deref;
+
+use rfc_dep_ietf::{DocIdentifier, Meta};
+
+use crate::project::DocCache;
+
+/* other names of the cached documents (Was, AlsoKnownAs) to their id in cache
+ * drafts are named whatever their revision */
+fn aliases(cache: &DocCache) -> HashMap<DocIdentifier, DocIdentifier> {
+    let mut aliases = HashMap::new();
+    for (id, state) in cache {
+        for (_, meta) in state.content.meta.deref() {
+            match meta {
+                Meta::Was(alias) => {
+                    aliases.insert(alias.unversioned(), id.clone());
+                }
+                Meta::AlsoKnownAs(set) => {
+                    aliases.extend(set.iter().map(|alias| (alias.unversioned(), id.clone())));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    aliases
+}
+
+/* id in cache of a document, or of the document it is an alias of */
+fn resolve(cache: &DocCache, aliases: &HashMap<DocIdentifier, DocIdentifier>, id: &DocIdentifier) -> DocIdentifier {
+    let id = id.unversioned();
+    match aliases.get(&id) {
+        Some(cached) if !cache.has_id(&id) => cached.clone(),
+        _ => id,
+    }
+}
+
+/* next document of a chain: the replacement of a draft, the latest rfc obsoleting a document
+ * or the rfc a draft became (the rfc which "Was" the draft) */
+fn successor(
+    cache: &DocCache,
+    aliases: &HashMap<DocIdentifier, DocIdentifier>,
+    id: &DocIdentifier,
+) -> Option<DocIdentifier> {
+    let state = cache.get(id)?;
+
+    let mut obsoleted_by = None;
+    for (_, meta) in state.content.meta.deref() {
+        match meta {
+            Meta::ReplacedBy(replacement) => return Some(resolve(cache, aliases, replacement)),
+            Meta::ObsoletedBy(set) => obsoleted_by = set.iter().map(|obsoleter| obsoleter.deref()).max(),
+            _ => {}
+        }
+    }
+
+    match obsoleted_by {
+        Some(obsoleter) => Some(resolve(cache, aliases, obsoleter)),
+        None if id.is_draft() => aliases.get(id).filter(|rfc| *rfc != id).cloned(),
+        None => None,
+    }
+}
+
+fn chain_with(cache: &DocCache, aliases: &HashMap<DocIdentifier, DocIdentifier>, id: &DocIdentifier) -> Vec<DocIdentifier> {
+    let mut chain = Vec::new();
+    let mut visited = HashSet::from([id.unversioned()]);
+    let mut current = id.unversioned();
+
+    // stops at the first document not superseded, not cached or already visited
+    while let Some(next) = successor(cache, aliases, &current) {
+        if !visited.insert(next.clone()) {
+            break;
+        }
+        chain.push(next.clone());
+        current = next;
+    }
+
+    chain
+}
+
+/* documents superseding a cached document, up to the current one (the head, last of the chain)
+ * follows ReplacedBy, ObsoletedBy and the rfc a draft became, other names (Was, AlsoKnownAs) are resolved to cached documents
+ * empty when the document isn't superseded, the head may not be cached */
+pub fn supersession_chain(cache: &DocCache, id: &DocIdentifier) -> Vec<DocIdentifier> {
+    chain_with(cache, &aliases(cache), id)
+}
+
+/* current document superseding a cached document, None if it isn't superseded */
+pub fn supersession_head(cache: &DocCache, id: &DocIdentifier) -> Option<DocIdentifier> {
+    supersession_chain(cache, id).pop()
+}
+
+/* supersession chain of every superseded document of the project */
+pub fn superseded_documents(cache: &DocCache) -> HashMap<DocIdentifier, Vec<DocIdentifier>> {
+    let aliases = aliases(cache);
+
+    cache
+        .keys()
+        .map(|id| (id.clone(), chain_with(cache, &aliases, id)))
+        .filter(|(_, chain)| !chain.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{id, ids, project, reference, references};
+
+    #[test]
+    fn expired_draft_to_current_rfc() {
+        // draft-a expired once replaced by draft-b, published as rfc9000, itself obsoleted by rfc9999
+        let cache = project(vec![
+            ("draft-a-02", vec![Meta::ReplacedBy(reference("draft-b"))]),
+            ("draft-b-05", vec![]),
+            (
+                "rfc9000",
+                vec![Meta::Was(reference("draft-b-05")), Meta::ObsoletedBy(references(&["rfc9999"]))],
+            ),
+        ]);
+
+        assert_eq!(supersession_chain(&cache, &id("draft-a")), ids(&["draft-b", "rfc9000", "rfc9999"]));
+        // the head isn't cached
+        assert_eq!(supersession_head(&cache, &id("draft-a")), Some(id("rfc9999")));
+        assert_eq!(supersession_chain(&cache, &id("draft-b")), ids(&["rfc9000", "rfc9999"]));
+
+        let superseded = superseded_documents(&cache);
+        assert_eq!(superseded.len(), 3);
+        assert_eq!(superseded[&id("rfc9000")], ids(&["rfc9999"]));
+    }
+
+    #[test]
+    fn aliases_are_resolved_to_cached_documents() {
+        // draft-b isn't cached, only the rfc it became
+        let cache = project(vec![
+            ("draft-a-02", vec![Meta::ReplacedBy(reference("draft-b-01"))]),
+            ("rfc9000", vec![Meta::Was(reference("draft-b-05"))]),
+        ]);
+
+        assert_eq!(supersession_chain(&cache, &id("draft-a")), ids(&["rfc9000"]));
+        assert!(supersession_chain(&cache, &id("rfc9000")).is_empty());
+    }
+
+    #[test]
+    fn replacements_before_obsoleting_rfcs() {
+        let cache = project(vec![(
+            "draft-a-02",
+            vec![
+                Meta::ObsoletedBy(references(&["rfc9000", "rfc9100"])),
+                Meta::ReplacedBy(reference("draft-b")),
+            ],
+        )]);
+        assert_eq!(supersession_chain(&cache, &id("draft-a")), ids(&["draft-b"]));
+
+        // the latest of the obsoleting rfcs
+        let cache = project(vec![("rfc8000", vec![Meta::ObsoletedBy(references(&["rfc9000", "rfc9100"]))])]);
+        assert_eq!(supersession_chain(&cache, &id("rfc8000")), ids(&["rfc9100"]));
+    }
+
+    #[test]
+    fn cycles_stop_the_chain() {
+        let cache = project(vec![
+            ("rfc8000", vec![Meta::ObsoletedBy(references(&["rfc9000"]))]),
+            ("rfc9000", vec![Meta::ObsoletedBy(references(&["rfc8000"]))]),
+        ]);
+
+        assert_eq!(supersession_chain(&cache, &id("rfc8000")), ids(&["rfc9000"]));
+        assert_eq!(supersession_chain(&cache, &id("rfc9000")), ids(&["rfc8000"]));
+    }
+
+    #[test]
+    fn documents_not_superseded() {
+        let cache = project(vec![("rfc9000", vec![Meta::Updates(references(&["rfc8000"]))])]);
+
+        assert_eq!(supersession_head(&cache, &id("rfc9000")), None);
+        // uncached documents have no known successor
+        assert!(supersession_chain(&cache, &id("rfc8000")).is_empty());
+        assert!(superseded_documents(&cache).is_empty());
+    }
+}
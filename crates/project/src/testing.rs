@@ -0,0 +1,51 @@
+/* Helpers of the unit tests */
+use std::collections::HashSet;
+
+use rfc_dep_ietf::{DocIdentifier, IetfDoc, Meta, MetaMap, Summary};
+
+use crate::doc::{DocReference, StatefulDoc};
+use crate::project::DocCache;
+
+pub(crate) fn id(name: &str) -> DocIdentifier {
+    name.parse().unwrap()
+}
+
+pub(crate) fn ids(names: &[&str]) -> Vec<DocIdentifier> {
+    names.iter().map(|name| id(name)).collect()
+}
+
+pub(crate) fn reference(name: &str) -> DocReference {
+    DocReference::from(id(name))
+}
+
+pub(crate) fn references(names: &[&str]) -> HashSet<DocReference> {
+    names.iter().map(|name| reference(name)).collect()
+}
+
+/* document with only its relations */
+pub(crate) fn doc(name: &str, metas: Vec<Meta<DocReference>>) -> StatefulDoc {
+    let id = id(name);
+    let summary = Summary::new(id.clone(), String::new(), id.is_rfc(), name.to_string()).unwrap();
+    let mut meta = MetaMap::default();
+    for relation in metas {
+        meta.insert(relation);
+    }
+
+    StatefulDoc::new(IetfDoc {
+        summary,
+        meta,
+        revisions: Vec::new(),
+        errata: None,
+    })
+}
+
+/* project of documents with only their relations, drafts are cached whatever the revision of their name */
+pub(crate) fn project(docs: Vec<(&str, Vec<Meta<DocReference>>)>) -> DocCache {
+    let mut cache = DocCache::default();
+    for (name, metas) in docs {
+        let doc = doc(name, metas);
+        cache.cache(doc.id(), doc);
+    }
+
+    cache
+}